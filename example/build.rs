@@ -1,4 +1,4 @@
 fn main() {
     eprintln!("=====> building...");
-    wire_build::configure().build();
+    wire_build::configure().graph(true).build();
 }
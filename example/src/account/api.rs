@@ -1,18 +1,33 @@
 use serde::Deserialize;
 use std::sync::Arc;
-use wire::{injectable, provider};
+use wire::{config, injectable, provider, Watch};
 
 use crate::account::domain::security::PasswordHasher;
 
 pub trait Hello {}
 
+#[provider(rename("wire.version"))]
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
 #[provider(config("account"))]
-#[derive(Clone, Debug, Default, Deserialize)]
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
 pub struct AccountConfig {
     pub addr: String,
     pub port: u16,
 }
 
+#[provider(config("limits"), reload)]
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+pub struct LimitsConfig {
+    pub requests_per_second: u32,
+}
+
+#[config("debug", profile = "dev")]
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+pub struct DebugConfig {
+    pub trace_requests: bool,
+}
+
 #[allow(dead_code)]
 #[injectable(export, rename("account_grpc_service"))]
 pub struct AccountGrpcServer {
@@ -20,4 +35,17 @@ pub struct AccountGrpcServer {
     config: AccountConfig,
     #[inject]
     password_hasher: Arc<dyn PasswordHasher>,
+    #[inject]
+    limits: Watch<LimitsConfig>,
+    #[inject(value = "wire.version")]
+    version: &'static str,
+    #[inject(value = "account.port")]
+    port: u16,
+}
+
+impl AccountGrpcServer {
+    /// Request limits, updated by `reload_config`
+    pub fn limits(&self) -> &Watch<LimitsConfig> {
+        &self.limits
+    }
 }
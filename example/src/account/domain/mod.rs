@@ -1 +1,2 @@
 pub mod security;
+pub mod user;
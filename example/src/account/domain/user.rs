@@ -0,0 +1,7 @@
+pub struct User {
+    pub name: String,
+}
+
+pub struct Session {
+    pub token: String,
+}
@@ -1 +1,2 @@
+pub mod repo;
 pub mod security;
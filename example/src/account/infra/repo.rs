@@ -0,0 +1,44 @@
+use std::marker::PhantomData;
+use std::sync::Arc;
+use std::time::Instant;
+use wire::{injectable, provider};
+
+use super::security::bcrypt_hasher::Connection;
+use crate::account::domain::user::User;
+
+#[provider]
+pub const MAX_CONNECTIONS: usize = 10;
+
+#[allow(dead_code)]
+#[provider]
+#[injectable]
+pub struct Repo<T> {
+    #[inject(manual)]
+    conn: Connection,
+    #[inject(value = "max_connections")]
+    max_connections: usize,
+
+    entity: PhantomData<T>,
+    #[init = "std::time::Instant::now"]
+    opened: Instant,
+}
+
+#[provider]
+#[injectable]
+pub struct UserRepo(#[inject] pub Arc<Repo<User>>);
+
+#[provider]
+#[injectable(constructor = "wire_new", vis = "pub(crate)")]
+pub struct IdGenerator;
+
+impl IdGenerator {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for IdGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -5,7 +5,7 @@ use serde::Deserialize;
 use std::io::Result;
 
 #[provider(config("bcrypt"))]
-#[derive(Clone, Debug, Default, Deserialize)]
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
 pub struct BcryptHasherConfig {
     pub cost: u32,
 }
@@ -13,15 +13,29 @@ pub struct BcryptHasherConfig {
 #[derive(Clone)]
 pub struct Connection {}
 
+#[derive(Clone)]
+pub struct Metrics {
+    pub prefix: String,
+}
+
+pub fn default_metrics() -> Metrics {
+    Metrics {
+        prefix: "bcrypt".to_string(),
+    }
+}
+
 #[allow(dead_code)]
 #[provider]
-#[injectable]
+#[injectable(builder)]
 pub struct BcryptHasher {
     #[inject]
     cfg: BcryptHasherConfig,
 
     #[inject(manual)]
     conn: Connection,
+
+    #[inject(manual, default = "default_metrics")]
+    metrics: Metrics,
 }
 
 #[provider]
@@ -1 +1,21 @@
+use std::sync::Arc;
+use wire::injectable;
 
+use crate::account::domain::security::PasswordHasher;
+use crate::account::domain::user::Session;
+use crate::account::infra::repo::{IdGenerator, Repo, UserRepo};
+
+#[allow(dead_code)]
+#[injectable(export(context = "worker"))]
+pub struct LoginWorker {
+    #[inject]
+    password_hasher: Arc<dyn PasswordHasher>,
+    #[inject]
+    users: Arc<UserRepo>,
+    #[inject]
+    ids: Arc<IdGenerator>,
+    #[inject]
+    sessions: Arc<Repo<Session>>,
+    #[default(3)]
+    max_attempts: u32,
+}
@@ -1,10 +1,25 @@
 use example::wire;
 
 fn main() {
-    let dep = wire::Dependency {
-        config: wire::Config::default(),
-        connection: example::account::infra::security::bcrypt_hasher::Connection {},
-    };
+    let config = load_config().unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        std::process::exit(2);
+    });
+    let dep = wire::Dependency::builder()
+        .config(config)
+        .connection(example::account::infra::security::bcrypt_hasher::Connection {})
+        .build()
+        .unwrap_or_else(|err| {
+            eprintln!("{}", err);
+            std::process::exit(2);
+        });
     wire::ServiceContext::new(&dep);
+    wire::WorkerContext::new(&dep);
     println!("Hello, world!");
 }
+
+fn load_config() -> Result<wire::Config, wire::ConfigError> {
+    let mut config = wire::Config::load(concat!(env!("CARGO_MANIFEST_DIR"), "/config.toml"))?;
+    config.apply_args(std::env::args().skip(1))?;
+    Ok(config)
+}
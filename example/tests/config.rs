@@ -0,0 +1,77 @@
+use example::wire::{Config, ConfigError};
+
+fn port(args: &[&str]) -> Result<u16, ConfigError> {
+    Config::from_args(args).map(|config| config.account.port)
+}
+
+#[test]
+fn reads_flag_with_separate_value() {
+    assert_eq!(port(&["--account.port", "9090"]), Ok(9090));
+}
+
+#[test]
+fn reads_flag_with_inline_value() {
+    assert_eq!(port(&["--account.port=9090"]), Ok(9090));
+}
+
+#[test]
+fn reads_set_pairs() {
+    assert_eq!(port(&["--set", "account.port=9090"]), Ok(9090));
+}
+
+#[test]
+fn applies_args_in_order() {
+    let mut config = Config::default();
+    config
+        .apply_args(["--account.addr", "0.0.0.0", "--set", "account.addr=[::]"])
+        .unwrap();
+    assert_eq!(config.account.addr, "[::]");
+}
+
+#[test]
+fn rejects_unknown_keys() {
+    assert_eq!(
+        port(&["--account.host", "localhost"]),
+        Err(ConfigError::UnknownKey("account.host".to_string()))
+    );
+    assert_eq!(
+        port(&["--set", "nothing.here=1"]),
+        Err(ConfigError::UnknownKey("nothing.here".to_string()))
+    );
+}
+
+#[test]
+fn rejects_values_of_another_type() {
+    assert!(matches!(
+        port(&["--account.port", "abc"]),
+        Err(ConfigError::Invalid { key, .. }) if key == "account.port"
+    ));
+    assert!(matches!(
+        port(&["--account.port=70000"]),
+        Err(ConfigError::Invalid { key, .. }) if key == "account.port"
+    ));
+}
+
+#[test]
+fn rejects_missing_values() {
+    assert_eq!(
+        port(&["--account.port"]),
+        Err(ConfigError::MissingValue("account.port".to_string()))
+    );
+    assert_eq!(
+        port(&["--set"]),
+        Err(ConfigError::MissingValue("set".to_string()))
+    );
+    assert_eq!(
+        port(&["--set", "account.port"]),
+        Err(ConfigError::MissingValue("account.port".to_string()))
+    );
+}
+
+#[test]
+fn rejects_positional_args() {
+    assert_eq!(
+        port(&["9090"]),
+        Err(ConfigError::UnexpectedArg("9090".to_string()))
+    );
+}
@@ -0,0 +1,37 @@
+use example::account::infra::security::bcrypt_hasher::Connection;
+use example::wire::{Config, Dependency, MissingDependencies};
+
+#[test]
+fn reports_every_missing_dependency() {
+    let missing = Dependency::builder().build().err().unwrap();
+    // `metrics` falls back to its `default`
+    assert_eq!(
+        missing,
+        MissingDependencies {
+            fields: vec!["config", "connection"],
+        }
+    );
+    assert_eq!(
+        missing.to_string(),
+        "missing dependencies: config, connection"
+    );
+}
+
+#[test]
+fn reports_the_remaining_missing_dependency() {
+    let missing = Dependency::builder()
+        .config(Config::default())
+        .build()
+        .err()
+        .unwrap();
+    assert_eq!(missing.fields, vec!["connection"]);
+}
+
+#[test]
+fn builds_with_defaults_for_optional_dependencies() {
+    let dep = Dependency::builder()
+        .config(Config::default())
+        .connection(Connection {})
+        .build();
+    assert!(dep.is_ok());
+}
@@ -0,0 +1,114 @@
+use std::sync::{Arc, Barrier, Mutex};
+use std::thread;
+
+use example::account::infra::security::bcrypt_hasher::Connection;
+use example::wire::{Config, Dependency, ReloadError, ServiceContext};
+
+fn context() -> ServiceContext {
+    let dep = Dependency::builder()
+        .config(Config::default())
+        .connection(Connection {})
+        .build()
+        .unwrap();
+    ServiceContext::new(&dep)
+}
+
+fn limits(requests_per_second: u32) -> Config {
+    let mut config = Config::default();
+    config.limits.requests_per_second = requests_per_second;
+    config
+}
+
+#[test]
+fn reload_updates_watches_and_notifies() {
+    let context = context();
+    let watch = context.account_grpc_service.limits();
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let recorded = seen.clone();
+    watch.subscribe(move |limits| recorded.lock().unwrap().push(limits.requests_per_second));
+
+    context.reload_config(limits(10)).unwrap();
+    assert_eq!(watch.get().requests_per_second, 10);
+    // unchanged sections don't notify
+    context.reload_config(limits(10)).unwrap();
+    context.reload_config(limits(20)).unwrap();
+    assert_eq!(*seen.lock().unwrap(), vec![10, 20]);
+}
+
+#[test]
+fn reload_rejects_changes_to_other_sections() {
+    let context = context();
+    let mut config = limits(10);
+    config.account.port = 9090;
+
+    assert_eq!(
+        context.reload_config(config),
+        Err(ReloadError { section: "account" })
+    );
+    // nothing of the rejected config is applied
+    assert_eq!(
+        context
+            .account_grpc_service
+            .limits()
+            .get()
+            .requests_per_second,
+        0
+    );
+    context.reload_config(limits(10)).unwrap();
+    assert_eq!(
+        context
+            .account_grpc_service
+            .limits()
+            .get()
+            .requests_per_second,
+        10
+    );
+}
+
+#[test]
+fn concurrent_reloads_leave_watches_on_the_config() {
+    let context = Arc::new(context());
+    let last = Arc::new(Mutex::new(None));
+    let recorded = last.clone();
+    context
+        .account_grpc_service
+        .limits()
+        .subscribe(move |limits| {
+            // a slow subscriber, notifications of both reloads overlap
+            thread::yield_now();
+            *recorded.lock().unwrap() = Some(limits.requests_per_second);
+        });
+
+    const ROUNDS: usize = 2000;
+    // each round two threads reload at once, then the watch is checked
+    let start = Arc::new(Barrier::new(3));
+    let done = Arc::new(Barrier::new(3));
+    let threads: Vec<_> = [1, 2]
+        .into_iter()
+        .map(|requests_per_second| {
+            let (context, start, done) = (context.clone(), start.clone(), done.clone());
+            thread::spawn(move || {
+                for _ in 0..ROUNDS {
+                    start.wait();
+                    context.reload_config(limits(requests_per_second)).unwrap();
+                    done.wait();
+                }
+            })
+        })
+        .collect();
+
+    let watch = context.account_grpc_service.limits();
+    for _ in 0..ROUNDS {
+        start.wait();
+        done.wait();
+        let current = watch.get().requests_per_second;
+        assert_eq!(*last.lock().unwrap(), Some(current));
+        // a watch behind the config would miss a reload to the config's value
+        let other = if current == 1 { 2 } else { 1 };
+        context.reload_config(limits(other)).unwrap();
+        assert_eq!(watch.get().requests_per_second, other);
+    }
+    for thread in threads {
+        thread.join().unwrap();
+    }
+}
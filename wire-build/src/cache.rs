@@ -0,0 +1,231 @@
+use std::{
+    collections::HashMap,
+    env,
+    ffi::OsString,
+    fs,
+    hash::{DefaultHasher, Hash, Hasher},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::ModuleContext;
+
+/// Modules parsed from each file, kept in `OUT_DIR/wire-cache.json` between build
+/// script runs so only changed files are parsed again.
+#[derive(Default, Serialize, Deserialize)]
+pub(crate) struct ParseCache {
+    /// cfg the modules were parsed with
+    cfg: u64,
+    files: HashMap<String, CachedFile>,
+    #[serde(skip)]
+    used: HashMap<String, CachedFile>,
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedFile {
+    modified: Option<SystemTime>,
+    len: u64,
+    hash: u64,
+    /// features of a dependency crate the modules were parsed with
+    features: Option<Vec<String>>,
+    modules: Vec<ModuleContext>,
+}
+
+impl ParseCache {
+    /// Read the cache of `out_dir`, without one nothing is cached.
+    pub(crate) fn load(out_dir: Option<&Path>) -> Self {
+        let Some(out_dir) = out_dir else {
+            return Self::default();
+        };
+        let path = out_dir.join("wire-cache.json");
+        let cfg = cfg_hash();
+        let mut cache = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<ParseCache>(&content).ok())
+            // items under `#[cfg]` differ when features change
+            .filter(|cache| cache.cfg == cfg)
+            .unwrap_or_default();
+        cache.cfg = cfg;
+        cache.path = Some(path);
+        cache
+    }
+
+    /// Modules of `path` mounted at `mods`, reused while the file keeps its mtime and
+    /// size or its content hash and is parsed with the same dependency `features`.
+    pub(crate) fn get_or_parse<F>(
+        &mut self,
+        mods: &[String],
+        path: &Path,
+        features: Option<&[String]>,
+        parse: F,
+    ) -> Vec<ModuleContext>
+    where
+        F: FnOnce(&str) -> Vec<ModuleContext>,
+    {
+        let key = format!("{}#{}", mods.join("::"), path.display());
+        let metadata =
+            fs::metadata(path).unwrap_or_else(|_| panic!("failed read file '{}'", path.display()));
+        let modified = metadata.modified().ok();
+
+        let features = features.map(<[String]>::to_vec);
+        let mut cached = self
+            .files
+            .remove(&key)
+            .filter(|file| file.features == features);
+        if let Some(file) = cached.as_ref() {
+            if file.modified.is_some() && file.modified == modified && file.len == metadata.len() {
+                let modules = file.modules.clone();
+                self.used.insert(key, cached.unwrap());
+                return modules;
+            }
+        }
+
+        let content = fs::read_to_string(path)
+            .unwrap_or_else(|_| panic!("failed read file '{}'", path.display()));
+        let mut hasher = DefaultHasher::new();
+        content.hash(&mut hasher);
+        let hash = hasher.finish();
+        let modules = match cached.take() {
+            // touched but unchanged
+            Some(file) if file.hash == hash => file.modules,
+            _ => parse(&content),
+        };
+        self.used.insert(
+            key,
+            CachedFile {
+                modified,
+                len: metadata.len(),
+                hash,
+                features,
+                modules: modules.clone(),
+            },
+        );
+        modules
+    }
+
+    /// Write the files used by this run, dropping removed ones.
+    pub(crate) fn save(self) {
+        let Some(path) = self.path.as_ref() else {
+            return;
+        };
+        let cache = ParseCache {
+            cfg: self.cfg,
+            files: self.used,
+            used: HashMap::new(),
+            path: None,
+        };
+        // a missing cache only costs a full parse next time
+        let _ = fs::write(path, serde_json::to_string(&cache).unwrap());
+    }
+}
+
+/// Hash of the cfg cargo passes to build scripts, read by `cfg_enabled`.
+fn cfg_hash() -> u64 {
+    // `env::vars` panics on any variable that isn't Unicode, also ones filtered out
+    let mut vars: Vec<(OsString, OsString)> = env::vars_os()
+        .filter(|(name, _)| {
+            let name = name.as_encoded_bytes();
+            name.starts_with(b"CARGO_FEATURE_") || name.starts_with(b"CARGO_CFG_")
+        })
+        .collect();
+    vars.sort();
+    let mut hasher = DefaultHasher::new();
+    vars.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::ENV;
+
+    /// Parse `files` in one run through the cache of `out_dir`, how many were parsed
+    fn parsed(out_dir: &Path, files: &[&Path], features: Option<&[String]>) -> usize {
+        let mut cache = ParseCache::load(Some(out_dir));
+        let mut parsed = 0;
+        for file in files {
+            cache.get_or_parse(&["crate".to_string()], file, features, |_| {
+                parsed += 1;
+                Vec::new()
+            });
+        }
+        cache.save();
+        parsed
+    }
+
+    #[test]
+    fn reparses_changed_files_only() {
+        let _env = ENV.lock().unwrap_or_else(|err| err.into_inner());
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("lib.rs");
+        let other = dir.path().join("other.rs");
+        fs::write(&file, "struct A;").unwrap();
+        fs::write(&other, "struct B;").unwrap();
+
+        assert_eq!(parsed(dir.path(), &[&file, &other], None), 2);
+        assert_eq!(parsed(dir.path(), &[&file, &other], None), 0);
+        // rewritten with the same content, matched by hash
+        fs::write(&file, "struct A;").unwrap();
+        assert_eq!(parsed(dir.path(), &[&file, &other], None), 0);
+        fs::write(&file, "struct AB;").unwrap();
+        assert_eq!(parsed(dir.path(), &[&file, &other], None), 1);
+    }
+
+    #[test]
+    fn reparses_when_cfg_changes() {
+        let _env = ENV.lock().unwrap_or_else(|err| err.into_inner());
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("lib.rs");
+        fs::write(&file, "struct A;").unwrap();
+
+        assert_eq!(parsed(dir.path(), &[&file], None), 1);
+        env::set_var("CARGO_FEATURE_WIRE_CACHE_TEST", "1");
+        let reparsed = parsed(dir.path(), &[&file], None);
+        env::remove_var("CARGO_FEATURE_WIRE_CACHE_TEST");
+        assert_eq!(reparsed, 1);
+    }
+
+    #[test]
+    fn reparses_when_dependency_features_change() {
+        let _env = ENV.lock().unwrap_or_else(|err| err.into_inner());
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("lib.rs");
+        fs::write(&file, "struct A;").unwrap();
+        let features = ["metrics".to_string()];
+
+        assert_eq!(parsed(dir.path(), &[&file], Some(&[])), 1);
+        assert_eq!(parsed(dir.path(), &[&file], Some(&features)), 1);
+        assert_eq!(parsed(dir.path(), &[&file], Some(&features)), 0);
+    }
+
+    #[test]
+    fn forgets_files_no_longer_used() {
+        let _env = ENV.lock().unwrap_or_else(|err| err.into_inner());
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("lib.rs");
+        let other = dir.path().join("other.rs");
+        fs::write(&file, "struct A;").unwrap();
+        fs::write(&other, "struct B;").unwrap();
+
+        assert_eq!(parsed(dir.path(), &[&file, &other], None), 2);
+        // a run without `other.rs` drops it from the cache
+        assert_eq!(parsed(dir.path(), &[&file], None), 0);
+        assert_eq!(parsed(dir.path(), &[&file, &other], None), 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn hashes_cfg_beside_non_unicode_variables() {
+        use std::os::unix::ffi::OsStringExt;
+
+        let _env = ENV.lock().unwrap_or_else(|err| err.into_inner());
+        env::set_var("WIRE_CACHE_TEST_BYTES", OsString::from_vec(vec![0xff]));
+        let hash = std::panic::catch_unwind(cfg_hash);
+        env::remove_var("WIRE_CACHE_TEST_BYTES");
+        assert!(hash.is_ok());
+    }
+}
@@ -0,0 +1,217 @@
+use std::fmt::Write;
+
+use serde::Serialize;
+
+use crate::Source;
+
+/// Resolved dependency graph
+#[derive(Debug, Default, Serialize)]
+pub struct Graph {
+    pub nodes: Vec<Node>,
+    pub edges: Vec<Edge>,
+}
+
+/// A provided type
+#[derive(Debug, Serialize)]
+pub struct Node {
+    /// absolute type path
+    pub id: String,
+    pub scope: NodeScope,
+    /// config section
+    pub config: Option<String>,
+    pub export: bool,
+    pub context: Option<String>,
+    pub source: Option<Source>,
+}
+
+/// How a node is provided
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NodeScope {
+    /// a section of `Config`
+    Config,
+    /// a field of the context
+    Export,
+    /// built once and shared by `Arc`
+    Shared,
+    /// supplied through `Dependency`
+    Manual,
+    /// a `#[provider] const`
+    Value,
+    /// injected without a provider nor `manual`, generation fails on it
+    Missing,
+}
+
+/// An injected field
+#[derive(Debug, Serialize)]
+pub struct Edge {
+    pub from: String,
+    pub to: String,
+    pub field: String,
+    /// `Arc`, `Watch`, ...
+    pub wrapper: Option<String>,
+    /// trait the field is declared as, for `dyn Trait` injects
+    pub trait_type: Option<String>,
+}
+
+impl Graph {
+    pub(crate) fn add_node(&mut self, node: Node) {
+        if !self.nodes.iter().any(|v| v.id == node.id) {
+            self.nodes.push(node);
+        }
+    }
+
+    fn index(&self, id: &str) -> Option<usize> {
+        self.nodes.iter().position(|v| v.id == id)
+    }
+
+    /// Node indices of an edge, `None` when an end isn't a node of the graph
+    fn edge_indices(&self, edge: &Edge) -> Option<(usize, usize)> {
+        Some((self.index(&edge.from)?, self.index(&edge.to)?))
+    }
+
+    fn edge_label(edge: &Edge) -> String {
+        match (&edge.wrapper, &edge.trait_type) {
+            (Some(wrapper), Some(trait_type)) => {
+                format!("{}: {}<dyn {}>", edge.field, wrapper, trait_type)
+            }
+            (Some(wrapper), None) => format!("{}: {}", edge.field, wrapper),
+            _ => edge.field.clone(),
+        }
+    }
+
+    /// Graphviz DOT
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph wire {\n    rankdir=LR;\n    node [shape=box];\n");
+        for (i, node) in self.nodes.iter().enumerate() {
+            let style = match node.scope {
+                NodeScope::Config => ", style=dashed",
+                NodeScope::Export => ", style=bold",
+                NodeScope::Shared => "",
+                NodeScope::Manual => ", style=dotted",
+                NodeScope::Value => ", shape=ellipse",
+                NodeScope::Missing => ", style=dotted, color=red",
+            };
+            let mut label = escape(&node.id);
+            if node.scope == NodeScope::Missing {
+                label.push_str(" (missing)");
+            }
+            writeln!(out, "    n{} [label=\"{}\"{}];", i, label, style).unwrap();
+        }
+        for edge in &self.edges {
+            let Some((from, to)) = self.edge_indices(edge) else {
+                writeln!(
+                    out,
+                    "    // skipped edge {} -> {}, not a node",
+                    edge.from, edge.to
+                )
+                .unwrap();
+                continue;
+            };
+            writeln!(
+                out,
+                "    n{} -> n{} [label=\"{}\"];",
+                from,
+                to,
+                escape(&Self::edge_label(edge))
+            )
+            .unwrap();
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// Mermaid flowchart
+    pub fn to_mermaid(&self) -> String {
+        let mut out = String::from("flowchart LR\n");
+        for (i, node) in self.nodes.iter().enumerate() {
+            let label = escape(&node.id);
+            let shape = match node.scope {
+                NodeScope::Config => format!("[/\"{}\"/]", label),
+                NodeScope::Export => format!("[[\"{}\"]]", label),
+                NodeScope::Shared => format!("[\"{}\"]", label),
+                NodeScope::Manual => format!("([\"{}\"])", label),
+                NodeScope::Value => format!("((\"{}\"))", label),
+                NodeScope::Missing => format!(">\"{} (missing)\"]", label),
+            };
+            writeln!(out, "    n{}{}", i, shape).unwrap();
+        }
+        for edge in &self.edges {
+            let Some((from, to)) = self.edge_indices(edge) else {
+                writeln!(
+                    out,
+                    "    %% skipped edge {} -> {}, not a node",
+                    edge.from, edge.to
+                )
+                .unwrap();
+                continue;
+            };
+            writeln!(
+                out,
+                "    n{} -->|\"{}\"| n{}",
+                from,
+                escape(&Self::edge_label(edge)),
+                to
+            )
+            .unwrap();
+        }
+        out
+    }
+
+    /// How the node matching `name`, an absolute path or a type name, is constructed
+    /// and who consumes it.
+    pub fn explain(&self, name: &str) -> Option<String> {
+        let suffix = format!("::{}", name);
+        let node = self
+            .nodes
+            .iter()
+            .find(|v| v.id == name || v.id.ends_with(&suffix))?;
+
+        let mut out = String::new();
+        let scope = match node.scope {
+            NodeScope::Config => format!(
+                "config section '{}'",
+                node.config.as_deref().unwrap_or_default()
+            ),
+            NodeScope::Export => match &node.context {
+                Some(context) => format!("exported by context '{}'", context),
+                None => "exported".to_string(),
+            },
+            NodeScope::Shared => "shared".to_string(),
+            NodeScope::Manual => "manual, supplied through Dependency".to_string(),
+            NodeScope::Value => "constant value".to_string(),
+            NodeScope::Missing => "missing, no provider and not manual".to_string(),
+        };
+        writeln!(out, "{} ({})", node.id, scope).unwrap();
+        if let Some(source) = &node.source {
+            writeln!(out, "  declared at {}:{}", source.file, source.line).unwrap();
+        }
+
+        let deps: Vec<_> = self.edges.iter().filter(|v| v.from == node.id).collect();
+        if !deps.is_empty() {
+            writeln!(out, "  constructed from:").unwrap();
+            for edge in deps {
+                writeln!(out, "    {} <- {}", Self::edge_label(edge), edge.to).unwrap();
+            }
+        }
+        let consumers: Vec<_> = self.edges.iter().filter(|v| v.to == node.id).collect();
+        if consumers.is_empty() {
+            writeln!(out, "  consumed by nothing").unwrap();
+        } else {
+            writeln!(out, "  consumed by:").unwrap();
+            for edge in consumers {
+                writeln!(out, "    {}.{}", edge.from, Self::edge_label(edge)).unwrap();
+            }
+        }
+        Some(out)
+    }
+
+    /// Machine readable JSON
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+}
+
+fn escape(label: &str) -> String {
+    label.replace('"', "'")
+}
@@ -1,10 +1,26 @@
-use heck::ToSnakeCase;
-use std::{cell::RefCell, collections::HashMap, env, ffi::OsStr, fs, path::PathBuf, vec};
+use heck::{ToSnakeCase, ToUpperCamelCase};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet, VecDeque},
+    env,
+    ffi::OsStr,
+    fs,
+    path::PathBuf,
+    vec,
+};
 
 use proc_macro2::TokenStream;
 use quote::quote;
+use serde::{Deserialize, Serialize};
+use wire_meta::{ConfigArgs, InjectArgs, ProviderArgs};
+
+use cache::ParseCache;
+
+mod cache;
+mod graph;
+pub use graph::{Edge, Graph, Node, NodeScope};
 use syn::{
-    parenthesized, parse_str, token, Attribute, Item, ItemImpl, ItemMod, ItemStruct, ItemUse, Path,
+    ext::IdentExt, parse_str, token, Attribute, Item, ItemImpl, ItemMod, ItemStruct, ItemUse, Path,
     Type, UseTree,
 };
 
@@ -13,12 +29,25 @@ pub fn configure() -> Builder {
         out_dir: None,
         out_file: None,
         dir: None,
+        crates: Vec::new(),
+        sets: Vec::new(),
+        graph: false,
+        build_script: true,
+        rustfmt: false,
+        profile: None,
+        graph_profiles: HashSet::new(),
         dep: build_ident("dep"),
         variants: RefCell::new(HashMap::new()),
         injectors: Vec::new(),
         providers: HashMap::new(),
         implements: HashMap::new(),
+        values: HashMap::new(),
+        paths: HashMap::new(),
         dependencies: RefCell::new(Vec::new()),
+        dependency_names: HashMap::new(),
+        context: None,
+        used: RefCell::new(HashSet::new()),
+        used_implements: RefCell::new(HashSet::new()),
     }
 }
 
@@ -26,12 +55,28 @@ pub struct Builder {
     pub(crate) out_dir: Option<PathBuf>,
     pub(crate) out_file: Option<String>,
     pub(crate) dir: Option<String>,
+    pub(crate) crates: Vec<(String, PathBuf, Vec<String>)>,
+    pub(crate) sets: Vec<PathBuf>,
+    pub(crate) graph: bool,
+    pub(crate) build_script: bool,
+    pub(crate) rustfmt: bool,
+    pub(crate) profile: Option<String>,
+    /// profiles gating providers other than config sections
+    graph_profiles: HashSet<String>,
     dep: proc_macro2::Ident,
     variants: RefCell<HashMap<String, Variant>>,
     injectors: Vec<Provider>,
     providers: HashMap<String, Provider>,
     implements: HashMap<String, Vec<String>>,
+    /// `#[provider] const` items by value name
+    values: HashMap<String, Provider>,
+    /// public paths of items defined behind private modules
+    paths: HashMap<String, String>,
     dependencies: RefCell<Vec<Dep>>,
+    dependency_names: HashMap<(Option<String>, String), proc_macro2::Ident>,
+    context: Option<String>,
+    used: RefCell<HashSet<String>>,
+    used_implements: RefCell<HashSet<(String, String)>>,
 }
 
 impl Builder {
@@ -45,20 +90,174 @@ impl Builder {
 
         self
     }
+    /// Source directory of the crate, defaults to `src`.
+    pub fn dir(mut self, dir: String) -> Self {
+        self.dir = Some(dir);
+
+        self
+    }
+    /// Scan a dependency crate for providers too, `path` is its package directory and
+    /// `name` the crate name generated code references it by. Feature cfgs of the
+    /// dependency are evaluated with `features`, the features cargo enables for it,
+    /// other cfgs with the target's.
+    pub fn add_crate(mut self, name: String, path: String, features: Vec<String>) -> Self {
+        self.crates.push((
+            name.replace('-', "_"),
+            PathBuf::from(path).join("src"),
+            features,
+        ));
+
+        self
+    }
+    /// Import a provider set manifest written by `Builder::build_set` of a library
+    /// crate, e.g. from the `DEP_<LINKS>_WIRE_SET` variable cargo passes for it, or
+    /// by `wire set` for libraries without `links`. Exports of the set stay out of
+    /// this crate's contexts.
+    pub fn include_set(mut self, path: String) -> Self {
+        self.sets.push(PathBuf::from(path));
+
+        self
+    }
+    /// Also write the dependency graph as `wire.dot`, `wire.mmd` and `wire.json`
+    /// next to the generated code.
+    pub fn graph(mut self, graph: bool) -> Self {
+        self.graph = graph;
+
+        self
+    }
+    /// Build the graph for a profile, defaults to the `APP_PROFILE` environment variable.
+    pub fn profile(mut self, profile: String) -> Self {
+        self.profile = Some(profile);
+
+        self
+    }
+    /// Reformat the generated file with the external `rustfmt` binary, e.g. to apply
+    /// a `rustfmt.toml`. A missing or failing rustfmt only warns.
+    pub fn rustfmt(mut self, rustfmt: bool) -> Self {
+        self.rustfmt = rustfmt;
+
+        self
+    }
+    /// Print `cargo:` directives, on by default. Turn it off when running outside
+    /// a build script, warnings then go to stderr.
+    pub fn build_script(mut self, build_script: bool) -> Self {
+        self.build_script = build_script;
+
+        self
+    }
 
     pub fn build(mut self) {
+        self.load();
+        let expanded = self.expand();
+        self.write(expanded);
+        if self.graph {
+            self.write_graph();
+        }
+    }
+
+    /// Resolve the graph and generate the code without writing it, panics on the
+    /// first error like `build`.
+    pub fn check(mut self) {
+        self.load();
+        self.expand();
+    }
+
+    /// Resolve the dependency graph without generating code.
+    pub fn resolve_graph(mut self) -> Graph {
+        self.load();
+        self.build_graph()
+    }
+
+    fn load(&mut self) {
         self.setup();
-        let modules = walk_dir(self.dir.as_ref().unwrap());
-        self.merge(modules);
+        // the graph depends on the profile
+        self.directive("cargo:rerun-if-env-changed=APP_PROFILE".to_string());
+        let set = self.collect_set("crate");
+        self.merge(set);
+    }
+
+    /// Providers of the scanned crate named `name`, the added crates and the
+    /// included sets.
+    fn collect_set(&self, name: &str) -> ProviderSet {
+        // cached in cargo's `OUT_DIR` only, `out_dir` may be a source directory
+        // for `wire gen`
+        let cache_dir = env::var_os("OUT_DIR").map(PathBuf::from);
+        let mut cache = ParseCache::load(cache_dir.as_deref());
+        let mut modules = parse_crate(&mut cache, self.dir.as_ref().unwrap(), name, None);
+        for (name, dir, features) in &self.crates {
+            modules.append(&mut parse_crate(&mut cache, dir, name, Some(features)));
+        }
+        cache.save();
+        self.rerun_if_changed(&modules);
+        let mut included = Vec::new();
+        for path in &self.sets {
+            self.directive(format!("cargo:rerun-if-changed={}", path.display()));
+            let content = fs::read_to_string(path)
+                .unwrap_or_else(|_| panic!("failed read provider set '{}'", path.display()));
+            let set: ProviderSet = serde_json::from_str(&content)
+                .unwrap_or_else(|_| panic!("failed parse provider set '{}'", path.display()));
+            included.push(set);
+        }
+        // included scopes resolve paths through re-exports of the library crates
+        let scopes = included.iter().flat_map(|set| set.scopes.clone()).collect();
+        let mut set = resolve_modules(modules, scopes);
+        for mut other in included {
+            set.append(&mut other);
+        }
+        set
+    }
+
+    fn expand(&mut self) -> TokenStream {
         let mut expanded = quote! {};
         expanded.extend(self.generate_config());
         expanded.extend(self.generate());
-        self.write(expanded);
+        expanded
+    }
+
+    /// Rerun only when a scanned source file changes, a new module needs a `mod`
+    /// item in one of them anyway.
+    fn rerun_if_changed(&self, modules: &[ModuleContext]) {
+        let mut files: Vec<&String> = modules.iter().map(|module| &module.file).collect();
+        files.sort();
+        files.dedup();
+        for file in files {
+            self.directive(format!("cargo:rerun-if-changed={}", file));
+        }
+    }
+
+    fn directive(&self, directive: String) {
+        if self.build_script {
+            println!("{}", directive);
+        }
+    }
+
+    /// Export the providers of a library crate as a manifest, named by the package
+    /// name, for consuming crates to `include_set` without scanning its sources.
+    /// Added crates and included sets are bundled too. The path is published as
+    /// `wire_set` metadata, read by dependents as `DEP_<LINKS>_WIRE_SET` when the
+    /// package sets `links`, packages without write it with `wire set` instead.
+    pub fn build_set(mut self) {
+        self.setup();
+        let name = env::var("CARGO_PKG_NAME").unwrap().replace('-', "_");
+        let path = self
+            .out_dir
+            .as_ref()
+            .expect("OUT_DIR is not set, set out_dir")
+            .join("wire-set.json");
+        fs::write(&path, self.set_manifest(&name)).unwrap();
+        self.directive(format!("cargo:wire_set={}", path.display()));
+    }
+
+    /// The manifest `build_set` writes, of the scanned crate named `name`.
+    pub fn set_manifest(&mut self, name: &str) -> String {
+        self.setup();
+        let set = self.collect_set(&name.replace('-', "_"));
+        serde_json::to_string_pretty(&set).unwrap()
     }
 
     fn setup(&mut self) {
         if self.out_dir.is_none() {
-            self.out_dir = Some(PathBuf::from(env::var("OUT_DIR").unwrap()));
+            self.out_dir = env::var("OUT_DIR").ok().map(PathBuf::from);
         }
         if self.out_file.is_none() {
             self.out_file = Some("wire.rs".to_string())
@@ -66,102 +265,790 @@ impl Builder {
         if self.dir.is_none() {
             self.dir = Some("src".to_string())
         }
+        if self.profile.is_none() {
+            self.profile = env::var("APP_PROFILE").ok().filter(|v| !v.is_empty());
+        }
     }
 
-    fn merge(&mut self, modules: Vec<ModuleContext>) {
-        for module in modules {
-            let mut module_injectors = module.injectors;
-            self.injectors.append(&mut module_injectors);
+    fn merge(&mut self, mut set: ProviderSet) {
+        // exports of other crates are fields of their contexts, not of this crate's
+        for provider in set.injectors.iter_mut().chain(set.providers.iter_mut()) {
+            if provider.module.first().map(String::as_str) != Some("crate") {
+                provider.metadata.export = false;
+                provider.metadata.context = None;
+            }
+        }
+        // providers of other profiles don't participate in the graph
+        let mut excluded = Vec::new();
+        for provider in set.injectors {
+            if self.in_profile(&provider.metadata) {
+                self.injectors.push(provider);
+            }
+        }
 
-            for (k, v) in module.providers {
-                self.providers.insert(k, v);
+        for provider in set.providers {
+            if self.in_profile(&provider.metadata) {
+                self.providers
+                    .insert(provider.struct_type.clone(), provider);
+            } else {
+                excluded.push(provider.struct_type);
             }
+        }
 
-            for (k, mut v) in module.implements {
-                if let Some(struct_types) = self.implements.get_mut(&k) {
-                    struct_types.append(&mut v);
-                } else {
-                    self.implements.insert(k, v);
-                };
+        for (k, implement) in set.implements {
+            if !self.in_profile(&implement.metadata) {
+                continue;
             }
+            if let Some(struct_types) = self.implements.get_mut(&k) {
+                struct_types.push(implement.struct_type);
+            } else {
+                self.implements.insert(k, vec![implement.struct_type]);
+            };
         }
+        for value in set.values {
+            if !self.in_profile(&value.metadata) {
+                continue;
+            }
+            let name = value.value_name();
+            if let Some(other) = self.values.get(&name) {
+                panic!(
+                    "value '{}' is provided by both '{}' and '{}'",
+                    name, other.struct_type, value.struct_type
+                )
+            }
+            self.values.insert(name, value);
+        }
+        self.paths.extend(set.paths);
+        self.injectors
+            .retain(|provider| !excluded.contains(&provider.struct_type));
+        self.instantiate_generics();
     }
 
-    fn format<T: AsRef<OsStr>>(&self, command: T) {
-        // rustfmt format code
-        let status = std::process::Command::new("rustfmt")
-            .arg(command)
-            .status()
-            .expect("Failed to run rustfmt");
+    /// Add a provider for each instantiation of a generic provider some provider
+    /// injects, `Repo<User>` is instantiated from `Repo<T>`.
+    fn instantiate_generics(&mut self) {
+        let mut pending: Vec<String> = self
+            .injectors
+            .iter()
+            .chain(self.providers.values())
+            .filter(|provider| provider.generics.is_empty())
+            .flat_map(|provider| provider.injects.iter())
+            .filter(|inject| !inject.trait_object && inject.value.is_none())
+            .map(|inject| inject.struct_type.clone())
+            .collect();
+        while let Some(struct_type) = pending.pop() {
+            if self.providers.contains_key(&struct_type) {
+                continue;
+            }
+            let (path, args) = split_type(&struct_type);
+            let Some(generic) = self.providers.get(&path) else {
+                continue;
+            };
+            if args.is_empty() || generic.generics.is_empty() {
+                continue;
+            }
+            let instance = generic.instantiate(&args);
+            pending.extend(
+                instance
+                    .injects
+                    .iter()
+                    .filter(|inject| !inject.trait_object)
+                    .map(|inject| inject.struct_type.clone()),
+            );
+            self.providers.insert(struct_type, instance);
+        }
+        self.name_instances();
+    }
 
-        if !status.success() {
-            panic!("Failed to format generated code with rustfmt");
+    /// Instantiations named alike by the last segment of their type arguments are
+    /// qualified by more segments, `Repo<a::User>` and `Repo<b::User>` get
+    /// `RepoAUser` and `RepoBUser`.
+    fn name_instances(&mut self) {
+        let mut instances: Vec<(String, String, Vec<String>)> = self
+            .providers
+            .keys()
+            .filter_map(|struct_type| {
+                let (path, args) = split_type(struct_type);
+                let generic = self.providers.get(&path)?;
+                (!args.is_empty() && !generic.generics.is_empty())
+                    .then(|| (struct_type.clone(), generic.ident.clone(), args))
+            })
+            .collect();
+        instances.sort();
+
+        let mut levels = vec![1; instances.len()];
+        let mut names: Vec<String> = instances
+            .iter()
+            .map(|(_, ident, args)| instance_ident(ident, args, 1))
+            .collect();
+        loop {
+            let mut qualified = false;
+            for i in 0..instances.len() {
+                let collides = (0..instances.len()).any(|j| j != i && names[j] == names[i]);
+                let (_, ident, args) = &instances[i];
+                if collides && instance_ident(ident, args, levels[i] + 1) != names[i] {
+                    levels[i] += 1;
+                    qualified = true;
+                }
+            }
+            for (i, (_, ident, args)) in instances.iter().enumerate() {
+                names[i] = instance_ident(ident, args, levels[i]);
+            }
+            if !qualified {
+                break;
+            }
+        }
+
+        for ((struct_type, ..), name) in instances.iter().zip(names) {
+            self.providers.get_mut(struct_type).unwrap().ident = name;
+        }
+    }
+
+    fn in_profile(&mut self, metadata: &Metadata) -> bool {
+        let Some(profile) = metadata.profile.as_ref() else {
+            return true;
+        };
+        // a config section only adds a field to `Config`, unknown sections are ignored
+        if metadata.config.is_none() {
+            self.graph_profiles.insert(profile.clone());
+        }
+        metadata.profile == self.profile
+    }
+
+    /// Pretty print in-process, unformatted output still compiles when it fails.
+    fn format(&self, token: &TokenStream) -> String {
+        match syn::parse2::<syn::File>(token.clone()) {
+            Ok(file) => prettyplease::unparse(&file),
+            Err(err) => {
+                self.warn(format!("failed to format generated code: {}", err));
+                token.to_string()
+            }
+        }
+    }
+
+    fn run_rustfmt<T: AsRef<OsStr>>(&self, path: T) {
+        match std::process::Command::new("rustfmt").arg(path).status() {
+            Ok(status) if status.success() => {}
+            Ok(status) => self.warn(format!("rustfmt failed with {}", status)),
+            Err(err) => self.warn(format!("failed to run rustfmt: {}", err)),
         }
     }
 
     fn write(&self, token: TokenStream) {
-        let out_dir = self.out_dir.as_ref().unwrap();
+        let out_dir = self
+            .out_dir
+            .as_ref()
+            .expect("OUT_DIR is not set, set out_dir");
         let di = out_dir.join(self.out_file.as_ref().unwrap());
-        fs::write(&di, token.to_string()).unwrap();
-        self.format(&di);
+        fs::write(&di, self.format(&token)).unwrap();
+        if self.rustfmt {
+            self.run_rustfmt(&di);
+        }
+    }
+
+    fn warn(&self, warning: String) {
+        if self.build_script {
+            println!("cargo:warning={}", warning);
+        } else {
+            eprintln!("warning: {}", warning);
+        }
+    }
+
+    fn write_graph(&self) {
+        let graph = self.build_graph();
+        let out_dir = self
+            .out_dir
+            .as_ref()
+            .expect("OUT_DIR is not set, set out_dir");
+        fs::write(out_dir.join("wire.dot"), graph.to_dot()).unwrap();
+        fs::write(out_dir.join("wire.mmd"), graph.to_mermaid()).unwrap();
+        fs::write(out_dir.join("wire.json"), graph.to_json()).unwrap();
+    }
+
+    /// Providers as nodes and injected fields as edges, trait objects point at the
+    /// bound implementation, unprovided manual injects at a `manual` node and other
+    /// unprovided injects at a `missing` one.
+    fn build_graph(&self) -> Graph {
+        let mut graph = Graph::default();
+        let mut providers: Vec<&Provider> = self.injectors.iter().collect();
+        providers.extend(self.providers.values().filter(|provider| {
+            !self
+                .injectors
+                .iter()
+                .any(|v| v.struct_type == provider.struct_type)
+        }));
+        // generic structs are drawn as their instantiations
+        providers.retain(|provider| provider.generics.is_empty());
+        providers.sort_by(|a, b| a.struct_type.cmp(&b.struct_type));
+
+        for provider in providers {
+            let scope = if provider.metadata.config.is_some() {
+                NodeScope::Config
+            } else if provider.metadata.export {
+                NodeScope::Export
+            } else {
+                NodeScope::Shared
+            };
+            graph.add_node(Node {
+                id: provider.struct_type.clone(),
+                scope,
+                config: provider.metadata.config.clone(),
+                export: provider.metadata.export,
+                context: provider.metadata.context.clone(),
+                source: provider.source.clone(),
+            });
+            for inject in &provider.injects {
+                let to = match self.extract_struct_type(inject) {
+                    // constants are nodes, config keys point at their section
+                    _ if inject.value.is_some() => {
+                        let name = inject.value.as_ref().unwrap();
+                        if let Some(value) = self.values.get(name) {
+                            graph.add_node(Node {
+                                id: value.struct_type.clone(),
+                                scope: NodeScope::Value,
+                                config: None,
+                                export: false,
+                                context: None,
+                                source: value.source.clone(),
+                            });
+                            value.struct_type.clone()
+                        } else {
+                            let Some(section) = name.rsplit_once('.').and_then(|(section, _)| {
+                                self.providers.values().find(|provider| {
+                                    provider.metadata.config.as_deref() == Some(section)
+                                })
+                            }) else {
+                                continue;
+                            };
+                            section.struct_type.clone()
+                        }
+                    }
+                    Some(struct_type) => struct_type,
+                    None => {
+                        let (id, scope) = if inject.manual {
+                            (inject.build_dep().key().1, NodeScope::Manual)
+                        } else {
+                            self.warn(format!(
+                                "provider missing for '{}' injected by '{}'",
+                                inject.struct_type, provider.struct_type
+                            ));
+                            (inject.struct_type.clone(), NodeScope::Missing)
+                        };
+                        graph.add_node(Node {
+                            id: id.clone(),
+                            scope,
+                            config: None,
+                            export: false,
+                            context: None,
+                            source: None,
+                        });
+                        id
+                    }
+                };
+                let wrapper = if inject.watch {
+                    Some("Watch".to_string())
+                } else {
+                    inject.wrapper_type.clone()
+                };
+                graph.edges.push(Edge {
+                    from: provider.struct_type.clone(),
+                    to,
+                    field: inject.field.clone(),
+                    wrapper,
+                    trait_type: inject.trait_object.then(|| inject.struct_type.clone()),
+                });
+            }
+        }
+        graph
     }
 
     fn generate_config(&self) -> TokenStream {
         let fields: Vec<_> = self
-            .providers
-            .values()
-            .filter_map(|provider| {
-                if let Some(name) = provider.metadata.config.as_ref() {
-                    let field_name = build_ident(name.as_str());
-                    let field_type: syn::Path = syn::parse_str(&provider.struct_type).unwrap();
-                    Some(quote! {
-                        pub #field_name: #field_type
-                    })
-                } else {
-                    None
+            .config_providers()
+            .into_iter()
+            .map(|provider| {
+                let name = provider.metadata.config.as_ref().unwrap();
+                let field_name = build_ident(name.as_str());
+                let field_type = self.visible_path(&provider.struct_type);
+                quote! {
+                    pub #field_name: #field_type
                 }
             })
             .collect();
 
+        // `section.field` setters used by command-line overrides
+        let mut keys = Vec::new();
+        let mut setters = Vec::new();
+        let mut sections = Vec::new();
+        for provider in self.config_providers() {
+            let name = provider.metadata.config.as_ref().unwrap();
+            let section_path = &build_config_path(name)[1..];
+            sections.push(quote! {
+                if let Some(value) = table.remove(#name) {
+                    config.#(#section_path).* = value.try_into().map_err(|err: toml::de::Error| {
+                        ConfigError::Invalid {
+                            key: #name.to_string(),
+                            message: err.message().to_string(),
+                        }
+                    })?;
+                }
+            });
+            for field in &provider.fields {
+                let key = format!("{}.{}", name, field.trim_start_matches("r#"));
+                let field: syn::Ident = syn::parse_str(field).unwrap();
+                setters.push(quote! {
+                    #key => self.#(#section_path).*.#field = parse_value(key, value)?,
+                });
+                keys.push(key);
+            }
+        }
+
+        // a crate without config fields has no key to set or value to parse
+        let set = if setters.is_empty() {
+            quote! {
+                pub fn set(&mut self, key: &str, _value: &str) -> Result<(), ConfigError> {
+                    Err(ConfigError::UnknownKey(key.to_string()))
+                }
+            }
+        } else {
+            quote! {
+                pub fn set(&mut self, key: &str, value: &str) -> Result<(), ConfigError> {
+                    match key {
+                        #(#setters)*
+                        _ => return Err(ConfigError::UnknownKey(key.to_string())),
+                    }
+                    Ok(())
+                }
+            }
+        };
+        let parse_value = if setters.is_empty() {
+            quote! {}
+        } else {
+            quote! {
+                fn parse_value<T: serde::de::DeserializeOwned>(key: &str, raw: &str) -> Result<T, ConfigError> {
+                    let parsed = toml::from_str::<toml::Table>(&format!("value = {}", raw))
+                        .ok()
+                        .and_then(|mut table| table.remove("value"));
+                    if let Some(Ok(value)) = parsed.map(toml::Value::try_into) {
+                        return Ok(value);
+                    }
+                    toml::Value::String(raw.to_string())
+                        .try_into()
+                        .map_err(|err: toml::de::Error| ConfigError::Invalid {
+                            key: key.to_string(),
+                            message: err.message().to_string(),
+                        })
+                }
+            }
+        };
+
+        let profile = match self.profile.as_ref() {
+            Some(profile) => quote! {Some(#profile)},
+            None => quote! {None},
+        };
+        // profiles gating providers change the graph, config must be loaded for the
+        // one it was built for, any other profile only selects an overlay
+        let profile_check = if self.graph_profiles.is_empty() {
+            quote! {}
+        } else {
+            let mut graph_profiles: Vec<_> = self.graph_profiles.iter().collect();
+            graph_profiles.sort();
+            quote! {
+                fn graph_profile(profile: Option<&str>) -> Option<&str> {
+                    profile.filter(|profile| [#(#graph_profiles),*].contains(profile))
+                }
+                if graph_profile(profile.as_deref()) != graph_profile(PROFILE) {
+                    return Err(ConfigError::Profile {
+                        expected: PROFILE.map(str::to_string),
+                        found: profile,
+                    });
+                }
+            }
+        };
+        // a crate without config sections reads nothing from the table
+        let from_table = if sections.is_empty() {
+            quote! {
+                pub fn from_table(_table: toml::Table) -> Result<Self, ConfigError> {
+                    Ok(Self::default())
+                }
+            }
+        } else {
+            quote! {
+                pub fn from_table(mut table: toml::Table) -> Result<Self, ConfigError> {
+                    let mut config = Self::default();
+                    #(#sections)*
+                    Ok(config)
+                }
+            }
+        };
+
         quote! {
             #[derive(Debug, Clone, Default, serde::Deserialize)]
             pub struct Config {
                 #(#fields),*
             }
+
+            /// Profile the service graph was built for, from `APP_PROFILE` at build time.
+            pub const PROFILE: Option<&str> = #profile;
+
+            impl Config {
+                /// Load a TOML file, layering `<name>.<profile>.toml` next to it when a
+                /// profile is selected by `APP_PROFILE`, defaulting to `PROFILE`.
+                pub fn load<P: AsRef<std::path::Path>>(path: P) -> Result<Self, ConfigError> {
+                    let path = path.as_ref();
+                    let profile = std::env::var("APP_PROFILE")
+                        .ok()
+                        .filter(|v| !v.is_empty())
+                        .or_else(|| PROFILE.map(str::to_string));
+                    #profile_check
+
+                    let mut table = read_table(path)?;
+                    if let Some(profile) = profile.as_ref() {
+                        let mut file_name = path.file_stem().unwrap_or_default().to_os_string();
+                        file_name.push(format!(".{}.toml", profile));
+                        let overlay = path.with_file_name(file_name);
+                        if overlay.exists() {
+                            merge_table(&mut table, read_table(&overlay)?);
+                        }
+                    }
+                    Self::from_table(table)
+                }
+
+                /// Build config from a TOML table, sections missing from the table keep
+                /// their defaults.
+                #from_table
+
+                /// Keys accepted by `Config::set`, as `section.field`.
+                pub const KEYS: &'static [&'static str] = &[#(#keys),*];
+
+                /// Build config from defaults overridden by command-line arguments.
+                pub fn from_args<I, S>(args: I) -> Result<Self, ConfigError>
+                where
+                    I: IntoIterator<Item = S>,
+                    S: AsRef<str>,
+                {
+                    let mut config = Self::default();
+                    config.apply_args(args)?;
+                    Ok(config)
+                }
+
+                /// Override keys from `--section.field value`, `--section.field=value`
+                /// or `--set section.field=value` arguments.
+                pub fn apply_args<I, S>(&mut self, args: I) -> Result<(), ConfigError>
+                where
+                    I: IntoIterator<Item = S>,
+                    S: AsRef<str>,
+                {
+                    let mut args = args.into_iter();
+                    while let Some(arg) = args.next() {
+                        let arg = arg.as_ref();
+                        let Some(flag) = arg.strip_prefix("--") else {
+                            return Err(ConfigError::UnexpectedArg(arg.to_string()));
+                        };
+                        let (key, value) = if flag == "set" {
+                            let Some(pair) = args.next() else {
+                                return Err(ConfigError::MissingValue(flag.to_string()));
+                            };
+                            let Some((key, value)) = pair.as_ref().split_once('=') else {
+                                return Err(ConfigError::MissingValue(pair.as_ref().to_string()));
+                            };
+                            (key.to_string(), value.to_string())
+                        } else if let Some((key, value)) = flag.split_once('=') {
+                            (key.to_string(), value.to_string())
+                        } else {
+                            let Some(value) = args.next() else {
+                                return Err(ConfigError::MissingValue(flag.to_string()));
+                            };
+                            (flag.to_string(), value.as_ref().to_string())
+                        };
+                        self.set(&key, &value)?;
+                    }
+                    Ok(())
+                }
+
+                /// Override a single `section.field` key, parsing the value as TOML
+                /// and falling back to a plain string.
+                #set
+            }
+
+            fn read_table(path: &std::path::Path) -> Result<toml::Table, ConfigError> {
+                let content = std::fs::read_to_string(path).map_err(|err| ConfigError::Read {
+                    path: path.display().to_string(),
+                    message: err.to_string(),
+                })?;
+                toml::from_str(&content).map_err(|err: toml::de::Error| ConfigError::Read {
+                    path: path.display().to_string(),
+                    message: err.message().to_string(),
+                })
+            }
+
+            fn merge_table(base: &mut toml::Table, overlay: toml::Table) {
+                for (key, value) in overlay {
+                    if let toml::Value::Table(overlay) = value {
+                        if let Some(toml::Value::Table(base)) = base.get_mut(&key) {
+                            merge_table(base, overlay);
+                            continue;
+                        }
+                        base.insert(key, toml::Value::Table(overlay));
+                    } else {
+                        base.insert(key, value);
+                    }
+                }
+            }
+
+            #parse_value
+
+            #[derive(Debug, Clone, PartialEq, Eq)]
+            pub enum ConfigError {
+                /// key is not a field of any config section
+                UnknownKey(String),
+                /// flag without a value
+                MissingValue(String),
+                /// argument is not a `--` flag
+                UnexpectedArg(String),
+                /// value doesn't match the field type
+                Invalid { key: String, message: String },
+                /// config file can't be read or isn't valid TOML
+                Read { path: String, message: String },
+                /// runtime profile selects other providers than the graph was built for
+                Profile {
+                    expected: Option<String>,
+                    found: Option<String>,
+                },
+            }
+
+            impl std::fmt::Display for ConfigError {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    match self {
+                        ConfigError::UnknownKey(key) => write!(f, "unknown config key '{}'", key),
+                        ConfigError::MissingValue(key) => write!(f, "missing value for '{}'", key),
+                        ConfigError::UnexpectedArg(arg) => write!(f, "unexpected argument '{}'", arg),
+                        ConfigError::Invalid { key, message } => {
+                            write!(f, "invalid value for '{}': {}", key, message)
+                        }
+                        ConfigError::Read { path, message } => {
+                            write!(f, "failed read config '{}': {}", path, message)
+                        }
+                        ConfigError::Profile { expected, found } => write!(
+                            f,
+                            "config profile '{}' doesn't match build profile '{}'",
+                            found.as_deref().unwrap_or("none"),
+                            expected.as_deref().unwrap_or("none")
+                        ),
+                    }
+                }
+            }
+
+            impl std::error::Error for ConfigError {}
         }
     }
 
     fn generate_dependencies(&self) -> TokenStream {
-        let mut dedup = HashMap::new();
-        let deps: Vec<_> = self
-            .dependencies
-            .borrow()
-            .iter()
-            .filter_map(|dep| {
-                let field_name = dep.ident.to_string();
-                if dedup.contains_key(&field_name) {
-                    None
-                } else {
-                    dedup.insert(field_name, true);
-                    Some(dep.build_field())
+        let mut dedup: HashMap<String, usize> = HashMap::new();
+        let mut deps: Vec<&Dep> = Vec::new();
+        let dependencies = self.dependencies.borrow();
+        for dep in dependencies.iter() {
+            let field_name = dep.ident.to_string();
+            if let Some(index) = dedup.get(&field_name) {
+                // optional when any inject site gives a fallback, fallbacks must agree
+                let first = deps[*index];
+                if first.default.is_some() && dep.default.is_some() && first.default != dep.default
+                {
+                    panic!(
+                        "conflicting defaults for manual dependency '{}'",
+                        field_name
+                    )
                 }
-            })
-            .collect();
+                if first.default.is_none() {
+                    deps[*index] = dep;
+                }
+            } else {
+                dedup.insert(field_name, deps.len());
+                deps.push(dep);
+            }
+        }
+
+        let mut idents = vec![build_ident("config")];
+        let mut types = vec![quote! {Config}];
+        let mut values = vec![quote! {self.config.unwrap()}];
+        let mut required = vec![build_ident("config")];
+        for dep in deps {
+            let ident = &dep.ident;
+            idents.push(ident.clone());
+            types.push(dep.build_type());
+            values.push(dep.build_value(quote! {self.#ident}));
+            if dep.default.is_none() {
+                required.push(ident.clone());
+            }
+        }
+        let names = required.iter().map(|ident| ident.to_string());
 
         quote! {
             pub struct Dependency {
-                pub config: Config,
+                #(pub #idents: #types),*
+            }
+
+            impl Dependency {
+                pub fn builder() -> DependencyBuilder {
+                    DependencyBuilder::default()
+                }
+            }
+
+            /// Builder for `Dependency`, `build` reports every required field left unset
+            /// and falls back to the default of optional manual dependencies.
+            #[derive(Default)]
+            pub struct DependencyBuilder {
+                #(#idents: Option<#types>),*
+            }
+
+            impl DependencyBuilder {
+                #(
+                    pub fn #idents(mut self, #idents: #types) -> Self {
+                        self.#idents = Some(#idents);
+                        self
+                    }
+                )*
+
+                pub fn build(self) -> Result<Dependency, MissingDependencies> {
+                    let mut fields = Vec::new();
+                    #(
+                        if self.#required.is_none() {
+                            fields.push(#names);
+                        }
+                    )*
+                    if !fields.is_empty() {
+                        return Err(MissingDependencies { fields });
+                    }
+
+                    Ok(Dependency {
+                        #(#idents: #values),*
+                    })
+                }
+            }
 
-                #(#deps),*
+            #[derive(Debug, Clone, PartialEq, Eq)]
+            pub struct MissingDependencies {
+                pub fields: Vec<&'static str>,
             }
+
+            impl std::fmt::Display for MissingDependencies {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    write!(f, "missing dependencies: {}", self.fields.join(", "))
+                }
+            }
+
+            impl std::error::Error for MissingDependencies {}
         }
     }
 
     fn generate(&mut self) -> TokenStream {
+        self.resolve_dependency_names();
+
+        // named contexts of `export(context = "...")`, then the default ServiceContext
+        let mut contexts: Vec<_> = self
+            .injectors
+            .iter()
+            .filter_map(|provider| provider.metadata.context.clone())
+            .collect();
+        contexts.sort();
+        contexts.dedup();
+        contexts.push(String::new());
+        let contexts: Vec<_> = contexts
+            .into_iter()
+            .map(|context| {
+                self.context = (!context.is_empty()).then_some(context);
+                self.generate_context()
+            })
+            .collect();
+        self.warn_unused();
+
+        // build dependencies
+        let dependency = self.generate_dependencies();
+        let reload_error = if self.reload_providers().is_empty() {
+            quote! {}
+        } else {
+            self.generate_reload_error()
+        };
+
+        quote! {
+            #reload_error
+
+            #dependency
+
+            #(#contexts)*
+        }
+    }
+
+    /// Report providers and `#[provider] impl` bindings of the building crate no
+    /// context constructs, dependency crates may provide more than a crate uses.
+    fn warn_unused(&self) {
+        let used = self.used.borrow();
+        let local = |struct_type: &str| struct_type.starts_with("crate::");
+        // generic structs are used through their instantiations
+        let is_used = |struct_type: &String| {
+            let generic = format!("{}<", struct_type);
+            used.contains(struct_type) || used.iter().any(|v| v.starts_with(&generic))
+        };
+        let mut warnings = Vec::new();
+        for provider in &self.injectors {
+            if local(&provider.struct_type) && !is_used(&provider.struct_type) {
+                warnings.push(format!(
+                    "injectable '{}' is never constructed, export it or inject it into an exported provider",
+                    provider.struct_type
+                ));
+            }
+        }
+        for provider in self.providers.values() {
+            // config sections are part of `Config` whether injected or not
+            if provider.metadata.config.is_none()
+                && local(&provider.struct_type)
+                && !is_used(&provider.struct_type)
+                && !self
+                    .injectors
+                    .iter()
+                    .any(|injector| injector.struct_type == provider.struct_type)
+            {
+                warnings.push(format!("provider '{}' is never used", provider.struct_type));
+            }
+        }
+        for (name, value) in &self.values {
+            if local(&value.struct_type) && !used.contains(&value.struct_type) {
+                warnings.push(format!("value '{}' is never used", name));
+            }
+        }
+        let used_implements = self.used_implements.borrow();
+        for (trait_type, struct_types) in &self.implements {
+            for struct_type in struct_types.iter().filter(|v| local(v)) {
+                if !used_implements.contains(&(trait_type.clone(), struct_type.clone())) {
+                    warnings.push(format!(
+                        "binding of '{}' to '{}' is never used",
+                        trait_type, struct_type
+                    ));
+                }
+            }
+        }
+        warnings.sort();
+        warnings.dedup();
+        for warning in warnings {
+            self.warn(warning);
+        }
+    }
+
+    /// Generate the context struct of `self.context`, it exports the injectors of the
+    /// context and constructs only what they depend on.
+    fn generate_context(&self) -> TokenStream {
+        self.variants.borrow_mut().clear();
         let injectors: Vec<_> = self
             .injectors
             .iter()
+            .filter(|provider| self.is_root(provider))
             .flat_map(|provider| {
+                if !provider.generics.is_empty() {
+                    panic!(
+                        "generic injectable '{}' can't be exported, inject an instantiation of it instead",
+                        provider.struct_type
+                    )
+                }
                 if self.variants.borrow().contains_key(&provider.struct_type) {
                     return None;
                 }
@@ -178,58 +1065,320 @@ impl Builder {
             if v.export {
                 let ident = &v.ident;
                 args.push(ident);
-                let field_type: syn::Path = syn::parse_str(k).unwrap();
+                let field_type = self.visible_path(k);
                 fields.push(quote! {
                     pub #ident: #field_type,
                 });
             }
         });
 
-        // build dependencies
-        let dependency = self.generate_dependencies();
-
+        let context = build_ident(&self.context_name());
         let dep = &self.dep;
-        quote! {
-            #dependency
+        let reloads = self.reload_providers();
+        if reloads.is_empty() {
+            return quote! {
+                pub struct #context{
+                    #(#fields)*
+                }
+
+                impl #context{
+                    // a context exporting only dependency-free injectables reads nothing
+                    #[allow(unused_variables)]
+                    pub fn new(#dep: &Dependency) -> Self {
+                        #(#injectors)*;
+
+                        Self{
+                            #(#args),*
+                        }
+                    }
+                }
+            };
+        }
+
+        // reloadable config sections, context keeps current config and section watches
+        let mut watches = Vec::new();
+        let mut watch_fields = Vec::new();
+        let mut watch_sets = Vec::new();
+        let mut watch_notifies = Vec::new();
+        let mut changed = Vec::new();
+        let mut reloads_check = Vec::new();
+        for provider in self.config_providers() {
+            let name = provider.metadata.config.as_ref().unwrap();
+            let config_path = build_config_path(name);
+            let section_path = &config_path[1..];
+            if !provider.metadata.reload {
+                reloads_check.push(quote! {
+                    if new.#(#section_path).* != config.#(#section_path).* {
+                        return Err(ReloadError { section: #name });
+                    }
+                });
+                continue;
+            }
+            let ident = build_watch_ident(name);
+            let field_type = self.visible_path(&provider.struct_type);
+            watches.push(quote! {
+                let #ident = ::wire::Watch::new(#dep.#(#config_path).*.clone());
+            });
+            watch_fields.push(quote! {
+                #ident: ::wire::Watch<#field_type>,
+            });
+            let section_changed = build_ident(&format!("{}_changed", ident));
+            watch_sets.push(quote! {
+                let #section_changed = config.#(#section_path).* != old.#(#section_path).*;
+                if #section_changed {
+                    self.#ident.set(config.#(#section_path).*.clone());
+                }
+            });
+            watch_notifies.push(quote! {
+                if #section_changed {
+                    self.#ident.notify();
+                }
+            });
+            changed.push(section_changed);
+        }
+        let idents = reloads
+            .iter()
+            .map(|provider| build_watch_ident(provider.metadata.config.as_ref().unwrap()));
 
-            pub struct ServiceContext{
+        quote! {
+            pub struct #context{
                 #(#fields)*
+
+                config: std::sync::Mutex<Config>,
+                #(#watch_fields)*
             }
 
-            impl ServiceContext{
+            impl #context{
                 pub fn new(#dep: &Dependency) -> Self {
+                    #(#watches)*
+
                     #(#injectors)*;
 
                     Self{
-                        #(#args),*
+                        #(#args,)*
+                        config: std::sync::Mutex::new(#dep.config.clone()),
+                        #(#idents),*
                     }
                 }
+
+                /// Swap reloadable config sections and notify their subscribers,
+                /// rejecting the reload if any non-reloadable section changed.
+                /// Sections are compared with `PartialEq` to find the changed ones.
+                pub fn reload_config(&self, new: Config) -> Result<(), ReloadError> {
+                    // watches are set under the lock so they end on the config's value
+                    // when reloads race, subscribers run after it's released and may
+                    // reload again
+                    let (#(#changed,)*) = {
+                        let mut config = self.config.lock().unwrap();
+                        #(#reloads_check)*
+                        let old = std::mem::replace(&mut *config, new);
+                        #(#watch_sets)*
+                        (#(#changed,)*)
+                    };
+                    #(#watch_notifies)*
+                    Ok(())
+                }
             }
         }
     }
+
+    fn context_name(&self) -> String {
+        match self.context.as_ref() {
+            Some(context) => format!("{}Context", context.to_upper_camel_case()),
+            None => "ServiceContext".to_string(),
+        }
+    }
+
+    /// exported by the context being generated
+    fn is_root(&self, provider: &Provider) -> bool {
+        provider.metadata.export && provider.metadata.context == self.context
+    }
+
+    fn config_providers(&self) -> Vec<&Provider> {
+        let mut providers: Vec<_> = self
+            .providers
+            .values()
+            .filter(|provider| provider.metadata.config.is_some())
+            .collect();
+        providers.sort_by(|a, b| a.metadata.config.cmp(&b.metadata.config));
+        providers
+    }
+
+    fn reload_providers(&self) -> Vec<&Provider> {
+        self.config_providers()
+            .into_iter()
+            .filter(|provider| provider.metadata.reload)
+            .collect()
+    }
+
+    fn generate_reload_error(&self) -> TokenStream {
+        quote! {
+            #[derive(Debug, Clone, PartialEq, Eq)]
+            pub struct ReloadError {
+                pub section: &'static str,
+            }
+
+            impl std::fmt::Display for ReloadError {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    write!(f, "config section '{}' is not reloadable", self.section)
+                }
+            }
+
+            impl std::error::Error for ReloadError {}
+        }
+    }
+    /// Name `Dependency` fields of manual injects, a derived name shared by different
+    /// types is qualified with module path segments until it's unique.
+    fn resolve_dependency_names(&mut self) {
+        let mut deps: Vec<Dep> = Vec::new();
+        // instantiated generics are providers only, their manual injects name `T`'s
+        // argument
+        let mut providers: Vec<&Provider> = self.injectors.iter().collect();
+        providers.extend(self.providers.values());
+        providers.retain(|provider| provider.generics.is_empty());
+        providers.sort_by(|a, b| a.struct_type.cmp(&b.struct_type));
+        for provider in providers {
+            for inject in &provider.injects {
+                if !inject.manual || self.extract_struct_type(inject).is_some() {
+                    continue;
+                }
+                let dep = inject.build_dep();
+                if !deps.iter().any(|v| v.key() == dep.key()) {
+                    deps.push(dep);
+                }
+            }
+        }
+
+        let mut levels = vec![1; deps.len()];
+        let mut names: Vec<String> = deps.iter().map(|dep| dep.ident.to_string()).collect();
+        loop {
+            let mut qualified = false;
+            for i in 0..deps.len() {
+                if deps[i].name.is_some() {
+                    continue;
+                }
+                let collides = DEPENDENCY_MEMBERS.contains(&names[i].as_str())
+                    || (0..deps.len()).any(|j| {
+                        deps[j].name.is_none()
+                            && names[j] == names[i]
+                            && deps[j].key().1 != deps[i].key().1
+                    });
+                let segments = deps[i].module_segments();
+                if collides && levels[i] < segments.len() {
+                    levels[i] += 1;
+                    qualified = true;
+                }
+            }
+            for (i, dep) in deps.iter().enumerate() {
+                if dep.name.is_none() {
+                    let segments = dep.module_segments();
+                    names[i] = segments[segments.len() - levels[i]..].join("_");
+                }
+            }
+            if !qualified {
+                break;
+            }
+        }
+
+        let mut types: HashMap<&str, String> = HashMap::new();
+        for (i, dep) in deps.iter().enumerate() {
+            let (_, field_type) = dep.key();
+            if DEPENDENCY_MEMBERS.contains(&names[i].as_str()) {
+                panic!(
+                    "manual dependency name '{}' of '{}' is taken by `Dependency`, set #[inject(manual, name = \"...\")] to rename it",
+                    names[i], field_type
+                )
+            }
+            if let Some(other) = types.get(names[i].as_str()) {
+                if *other != field_type {
+                    panic!(
+                        "manual dependency name '{}' is shared by '{}' and '{}', set #[inject(manual, name = \"...\")] to tell them apart",
+                        names[i], other, field_type
+                    )
+                }
+            }
+            types.insert(names[i].as_str(), field_type);
+        }
+
+        self.dependency_names = deps
+            .iter()
+            .zip(names.iter())
+            .map(|(dep, name)| (dep.key(), build_ident(name)))
+            .collect();
+    }
+
     fn extract_struct_type(&self, inject: &Inject) -> Option<String> {
         if inject.trait_object {
-            if let Some(struct_types) = self.implements.get(&inject.struct_type) {
-                Some(struct_types.first().unwrap().clone())
-            } else {
-                None
-            }
+            self.implements
+                .get(&inject.struct_type)
+                .and_then(|struct_types| struct_types.first().cloned())
         } else if self.providers.contains_key(&inject.struct_type) {
             Some(inject.struct_type.clone())
         } else {
             None
         }
     }
+    /// Path generated code names an item by, its shortest public re-export when it's
+    /// defined behind a private module. Type arguments are mapped too.
+    fn visible(&self, path: &str) -> String {
+        let ty: Type = parse_str(path).unwrap_or_else(|_| panic!("failed parse type '{}'", path));
+        map_type(&ty, true, &mut |path, _| {
+            self.paths.get(&path).cloned().unwrap_or(path)
+        })
+    }
+
+    fn visible_path(&self, path: &str) -> syn::Path {
+        let visible = self.visible(path);
+        parse_str(&visible).unwrap_or_else(|_| panic!("failed parse path '{}'", visible))
+    }
+
+    /// Expression of a named value, a `#[provider] const` first, else a config key
+    fn build_value(&self, name: &str) -> TokenStream {
+        if let Some(value) = self.values.get(name) {
+            self.used.borrow_mut().insert(value.struct_type.clone());
+            let path = self.visible_path(&value.struct_type);
+            return quote! {#path};
+        }
+        let Some((section, field)) = name.rsplit_once('.') else {
+            panic!(
+                "value '{}' not found, provide it with `#[provider] const`",
+                name
+            )
+        };
+        let Some(provider) = self
+            .providers
+            .values()
+            .find(|provider| provider.metadata.config.as_deref() == Some(section))
+        else {
+            panic!(
+                "value '{}' not found, no config section '{}'",
+                name, section
+            )
+        };
+        if !provider.fields.iter().any(|v| v == field) {
+            panic!(
+                "value '{}' not found, '{}' has no visible field '{}'",
+                name, provider.struct_type, field
+            )
+        }
+        let dep = &self.dep;
+        let ident_parts = build_config_path(name);
+        // the field may be `Copy`, where a method call `clone` trips clippy
+        quote! {::core::clone::Clone::clone(&#dep.#(#ident_parts).*)}
+    }
+
     fn build_provider(&self, provider: &Provider) -> (TokenStream, TokenStream) {
-        eprintln!("building provider: {:?}", provider);
         // create provider deps
         let mut deps: Vec<TokenStream> = Vec::new();
         let args: Vec<_> = provider
             .injects
             .iter()
             .map(|inject| {
+                if let Some(name) = inject.value.as_ref() {
+                    return self.build_value(name);
+                }
                 // check dep if provided
-                let struct_type = self.extract_struct_type(&inject);
+                let struct_type = self.extract_struct_type(inject);
                 let provided = struct_type.is_some();
 
                 // provider not found
@@ -239,7 +1388,23 @@ impl Builder {
 
                 // provider manual inject provider
                 if !provided && inject.manual {
-                    let dep = inject.build_dep();
+                    let mut dep = inject.build_dep();
+                    dep.ident = self
+                        .dependency_names
+                        .get(&dep.key())
+                        .unwrap_or_else(|| {
+                            panic!(
+                                "manual dependency '{}' of '{}' has no `Dependency` field",
+                                dep.key().1,
+                                provider.struct_type
+                            )
+                        })
+                        .clone();
+                    // named by the type, the generated field by the visible path
+                    dep.path = self.visible_path(&inject.struct_type);
+                    if let Some(DefaultValue::Factory(factory)) = dep.default.as_mut() {
+                        *factory = self.visible(factory);
+                    }
                     let param = dep.build_param(&self.dep);
                     self.dependencies.borrow_mut().push(dep);
                     return param;
@@ -247,6 +1412,27 @@ impl Builder {
 
                 // find struct define type
                 let struct_type = &struct_type.unwrap();
+                if inject.trait_object {
+                    self.used_implements
+                        .borrow_mut()
+                        .insert((inject.struct_type.clone(), struct_type.clone()));
+                }
+
+                // reloadable config section, share the section watch
+                if inject.watch {
+                    let provider = self.providers.get(struct_type).unwrap();
+                    let Some(name) = provider.metadata.config.as_ref() else {
+                        panic!("'{}' is not a config provider, can't inject it as Watch", struct_type)
+                    };
+                    if !provider.metadata.reload {
+                        panic!(
+                            "config section '{}' is not reloadable, mark '{}' with `reload` to inject it as Watch",
+                            name, struct_type
+                        )
+                    }
+                    let ident = build_watch_ident(name);
+                    return quote! {#ident.clone()};
+                }
                 // build from cache
                 if let Some(variant) = self.variants.borrow().get(struct_type) {
                     let ident = variant.ident.clone();
@@ -263,9 +1449,7 @@ impl Builder {
 
         // config provider
         if let Some(name) = provider.metadata.config.as_ref() {
-            let mut parts = vec!["config"];
-            parts.extend(name.split('.'));
-            let ident_parts: Vec<_> = parts.into_iter().map(build_ident).collect();
+            let ident_parts = build_config_path(name);
             let dep = &self.dep;
             return (quote! {}, quote! {#dep.#(#ident_parts).*});
         }
@@ -276,28 +1460,31 @@ impl Builder {
             provider.ident.to_snake_case()
         };
         let ident = build_ident(name.as_str());
+        self.used.borrow_mut().insert(provider.struct_type.clone());
         self.variants.borrow_mut().insert(
             provider.struct_type.clone(),
             Variant {
                 ident: ident.clone(),
-                export: provider.metadata.export,
+                export: self.is_root(provider),
             },
         );
-        let path: syn::Path = parse_str(&provider.struct_type).expect(&format!(
-            "failed parse struct type '{}' to path",
-            &provider.struct_type
-        ));
-        eprintln!("build provider: {:?}", provider);
-        let assign = if provider.metadata.export {
+        let mut path = self.visible_path(&provider.struct_type);
+        // `Repo::<User>::new` in expression position
+        if let syn::PathArguments::AngleBracketed(args) =
+            &mut path.segments.last_mut().unwrap().arguments
+        {
+            args.colon2_token = Some(Default::default());
+        }
+        let constructor = build_ident(provider.metadata.constructor.as_deref().unwrap_or("new"));
+        let assign = if self.is_root(provider) {
             quote! {
-                let #ident = #path::new(#(#args),*);
+                let #ident = #path::#constructor(#(#args),*);
             }
         } else {
             quote! {
-                let #ident = std::sync::Arc::new(#path::new(#(#args),*));
+                let #ident = std::sync::Arc::new(#path::#constructor(#(#args),*));
             }
         };
-        eprintln!("build provider '{}' success", provider.ident.to_string());
         (
             quote! {
                 #(#deps)*
@@ -309,165 +1496,576 @@ impl Builder {
     }
 }
 
-fn walk_dir(dir: &str) -> Vec<ModuleContext> {
-    let mut modules: Vec<ModuleContext> = Vec::new();
+/// Providers of a crate with resolved paths, before profile selection
+#[derive(Default, Serialize, Deserialize)]
+struct ProviderSet {
+    providers: Vec<Provider>,
+    injectors: Vec<Provider>,
+    /// `#[provider] impl` bindings by trait
+    implements: Vec<(String, Provider)>,
+    /// `#[provider] const` items
+    values: Vec<Provider>,
+    /// module scopes, resolves paths through the crate's re-exports
+    scopes: HashMap<String, Scope>,
+    /// public paths of items defined behind private modules, by definition path
+    #[serde(default)]
+    paths: HashMap<String, String>,
+}
+
+impl ProviderSet {
+    fn append(&mut self, other: &mut ProviderSet) {
+        self.providers.append(&mut other.providers);
+        self.injectors.append(&mut other.injectors);
+        self.implements.append(&mut other.implements);
+        self.values.append(&mut other.values);
+        self.paths.extend(other.paths.drain());
+    }
+}
 
-    for res in std::fs::read_dir(dir).unwrap() {
-        let entry = res.unwrap();
-        let path = entry.path();
-        if path.is_dir() {
-            modules.append(walk_dir(path.to_str().unwrap()).as_mut());
-        } else if path.extension().map_or(false, |ext| ext == "rs") {
-            let mods = parse_file_path(path.as_path());
-            let content = std::fs::read_to_string(&path)
-                .expect(&format!("failed read file '{}'", path.display()));
-            let ast = syn::parse_file(&content)
-                .expect(&format!("failed parse file '{}'", path.display()));
-            modules.append(parse_module(mods, ast.items).as_mut());
+fn resolve_modules(modules: Vec<ModuleContext>, scopes: HashMap<String, Scope>) -> ProviderSet {
+    let mut resolver = Resolver::new(&modules);
+    let mut set = ProviderSet {
+        scopes: resolver.scopes.clone(),
+        ..Default::default()
+    };
+    resolver.scopes.extend(scopes);
+    set.paths = resolver.visible_paths();
+    for module in modules {
+        for mut provider in module.injectors {
+            resolver.resolve_provider(&mut provider);
+            set.injectors.push(provider);
         }
+
+        for (_, mut provider) in module.providers {
+            resolver.resolve_provider(&mut provider);
+            set.providers.push(provider);
+        }
+
+        for (k, v) in module.implements {
+            let k = resolver.resolve_type(&module.mods, &k, &[]);
+            for mut implement in v {
+                implement.struct_type =
+                    resolver.resolve_type(&module.mods, &implement.struct_type, &[]);
+                set.implements.push((k.clone(), implement));
+            }
+        }
+        set.values.extend(module.values);
+    }
+    set
+}
+
+/// Parse the crate from its root file, following `mod` declarations. Modules are
+/// named from `name`, `crate` for the building crate. `features` are the enabled
+/// features of a dependency crate, `None` for the building crate whose features
+/// cargo passes to the build script.
+fn parse_crate<P: AsRef<std::path::Path>>(
+    cache: &mut ParseCache,
+    dir: P,
+    name: &str,
+    features: Option<&[String]>,
+) -> Vec<ModuleContext> {
+    let dir = dir.as_ref();
+    let root = ["lib.rs", "main.rs"]
+        .iter()
+        .map(|file| dir.join(file))
+        .find(|path| path.exists())
+        .unwrap_or_else(|| panic!("crate root missing in '{}'", dir.display()));
+    parse_file(cache, vec![name.to_string()], &root, true, features)
+}
+
+/// Parse a module file and the files of its out-of-line modules, `mod_rs` for crate
+/// roots, `mod.rs` and `#[path]` files whose child modules live next to them instead
+/// of in a directory named after the module.
+fn parse_file(
+    cache: &mut ParseCache,
+    mods: Vec<String>,
+    path: &std::path::Path,
+    mod_rs: bool,
+    features: Option<&[String]>,
+) -> Vec<ModuleContext> {
+    let mut modules = cache.get_or_parse(&mods, path, features, |content| {
+        parse_source(mods.clone(), path, mod_rs, content, features)
+    });
+    let decls: Vec<ModDecl> = modules
+        .iter_mut()
+        .flat_map(|module| module.decls.drain(..))
+        .collect();
+    for decl in decls {
+        let (file, mod_rs) = decl.find_file();
+        modules.append(&mut parse_file(cache, decl.mods, &file, mod_rs, features));
     }
 
     modules
 }
 
-fn parse_module(mods: Vec<String>, items: Vec<syn::Item>) -> Vec<ModuleContext> {
+/// Modules declared in one file, out-of-line modules are left as `decls`.
+fn parse_source(
+    mods: Vec<String>,
+    path: &std::path::Path,
+    mod_rs: bool,
+    content: &str,
+    features: Option<&[String]>,
+) -> Vec<ModuleContext> {
+    let wire = has_wire_attrs(content);
+    let ast = if wire {
+        syn::parse_file(content).ok()
+    } else {
+        parse_skeleton(content)
+    };
+    let ast = ast.unwrap_or_else(|| panic!("failed parse file '{}'", path.display()));
+
+    let file_dir = path.parent().unwrap().to_path_buf();
+    let dir = if mod_rs {
+        file_dir.clone()
+    } else {
+        file_dir.join(path.file_stem().unwrap())
+    };
+    let file = path.to_path_buf();
+    parse_module(
+        mods,
+        ast.items,
+        ModuleDir {
+            dir,
+            file_dir,
+            file,
+        },
+        wire,
+        features,
+    )
+}
+
+/// Cheap pre-scan, files never naming a wire attribute only contribute names and
+/// modules for path resolution. Attributes written in comments or strings only
+/// cost a full parse, `# [provider]` with a space or a path like `#[wire::provider]`
+/// aren't read by the build either.
+fn has_wire_attrs(content: &str) -> bool {
+    ["#[provider", "#[injectable", "#[config"]
+        .iter()
+        .any(|attr| content.contains(attr))
+}
+
+/// Parse a file for its item names, `use` and `mod` items with the other item
+/// bodies emptied, falling back to a full parse for tokens the skeleton breaks.
+fn parse_skeleton(content: &str) -> Option<syn::File> {
+    let tokens: TokenStream = content.parse().ok()?;
+    syn::parse2(skeleton(tokens))
+        .or_else(|_| syn::parse_file(content))
+        .ok()
+}
+
+/// Empty the brace groups of items, function bodies and fields hold no names a
+/// path resolves through. Groups of `use` trees are kept and inline modules are
+/// emptied in turn.
+fn skeleton(tokens: TokenStream) -> TokenStream {
+    use proc_macro2::{Delimiter, Group, TokenTree};
+
+    // last keyword of the current item, items end at `;` or a brace group
+    let mut keyword = None;
+    tokens
+        .into_iter()
+        .map(|token| match token {
+            TokenTree::Punct(punct) if punct.as_char() == ';' => {
+                keyword = None;
+                TokenTree::Punct(punct)
+            }
+            TokenTree::Ident(ident) => {
+                let name = ident.to_string();
+                if [
+                    "use", "mod", "fn", "struct", "enum", "trait", "impl", "const", "static",
+                    "type",
+                ]
+                .contains(&name.as_str())
+                {
+                    keyword = Some(name);
+                }
+                TokenTree::Ident(ident)
+            }
+            TokenTree::Group(group) if group.delimiter() == Delimiter::Brace => {
+                let stream = match keyword.take().as_deref() {
+                    Some("use") => group.stream(),
+                    Some("mod") => skeleton(group.stream()),
+                    _ => TokenStream::new(),
+                };
+                let mut emptied = Group::new(Delimiter::Brace, stream);
+                emptied.set_span(group.span());
+                TokenTree::Group(emptied)
+            }
+            token => token,
+        })
+        .collect()
+}
+
+/// Out-of-line `mod foo;`, found on disk after parsing so cached files still follow
+/// module files added or moved since.
+#[derive(Clone, Serialize, Deserialize)]
+struct ModDecl {
+    mods: Vec<String>,
+    name: String,
+    /// `#[path]` file
+    path: Option<PathBuf>,
+    /// `mod foo;` lookup directory
+    dir: PathBuf,
+}
+
+impl ModDecl {
+    /// The module file and whether it owns its directory like `mod.rs`
+    fn find_file(&self) -> (PathBuf, bool) {
+        // `#[path]` files own their directory like `mod.rs`
+        if let Some(path) = &self.path {
+            return (path.clone(), true);
+        }
+        let file = self.dir.join(format!("{}.rs", self.name));
+        if file.exists() {
+            return (file, false);
+        }
+        let file = self.dir.join(&self.name).join("mod.rs");
+        if file.exists() {
+            return (file, true);
+        }
+        panic!(
+            "file for module '{}' not found in '{}'",
+            self.mods.join("::"),
+            self.dir.display()
+        )
+    }
+}
+
+/// Directories out-of-line modules are resolved against
+#[derive(Clone)]
+struct ModuleDir {
+    /// `mod foo;` lookup directory, `foo.rs` or `foo/mod.rs`
+    dir: PathBuf,
+    /// `#[path]` base directory
+    file_dir: PathBuf,
+    /// source file of the module
+    file: PathBuf,
+}
+
+fn parse_module(
+    mods: Vec<String>,
+    items: Vec<syn::Item>,
+    dir: ModuleDir,
+    wire: bool,
+    features: Option<&[String]>,
+) -> Vec<ModuleContext> {
     let mut modules = Vec::new();
-    let mut module = ModuleContext::new(mods);
+    let mut module = ModuleContext::new(mods, dir.file.display().to_string());
     for item in items {
+        if !cfg_enabled(item_attrs(&item), features) {
+            continue;
+        }
+        if let Some(ident) = item_ident(&item) {
+            let name = ident.unraw().to_string();
+            if item_vis(&item).is_some_and(is_public) {
+                module.exports.insert(name.clone());
+            }
+            module.items.insert(name);
+        }
         match item {
             Item::Mod(item_mod) => {
-                modules.append(module.parse_item_mod(item_mod).as_mut());
+                modules.append(
+                    module
+                        .parse_item_mod(item_mod, &dir, wire, features)
+                        .as_mut(),
+                );
             }
             Item::Use(item_use) => {
                 module.parse_item_use(item_use);
             }
-            Item::Struct(item_struct) => {
+            Item::Struct(item_struct) if wire => {
                 module.parse_item_struct(item_struct);
             }
-            Item::Impl(item_impl) => {
+            Item::Impl(item_impl) if wire => {
                 module.parse_item_impl(item_impl);
             }
+            Item::Const(item_const) if wire => {
+                module.parse_item_const(item_const);
+            }
             _ => {}
         }
     }
-    modules.push(module);
-
-    modules
+    modules.push(module);
+
+    modules
+}
+
+fn item_ident(item: &Item) -> Option<&syn::Ident> {
+    match item {
+        Item::Const(item) => Some(&item.ident),
+        Item::Enum(item) => Some(&item.ident),
+        Item::Fn(item) => Some(&item.sig.ident),
+        Item::Mod(item) => Some(&item.ident),
+        Item::Static(item) => Some(&item.ident),
+        Item::Struct(item) => Some(&item.ident),
+        Item::Trait(item) => Some(&item.ident),
+        Item::TraitAlias(item) => Some(&item.ident),
+        Item::Type(item) => Some(&item.ident),
+        Item::Union(item) => Some(&item.ident),
+        _ => None,
+    }
+}
+
+fn item_vis(item: &Item) -> Option<&syn::Visibility> {
+    match item {
+        Item::Const(item) => Some(&item.vis),
+        Item::Enum(item) => Some(&item.vis),
+        Item::Fn(item) => Some(&item.vis),
+        Item::Mod(item) => Some(&item.vis),
+        Item::Static(item) => Some(&item.vis),
+        Item::Struct(item) => Some(&item.vis),
+        Item::Trait(item) => Some(&item.vis),
+        Item::TraitAlias(item) => Some(&item.vis),
+        Item::Type(item) => Some(&item.vis),
+        Item::Union(item) => Some(&item.vis),
+        Item::Use(item) => Some(&item.vis),
+        _ => None,
+    }
+}
+
+/// Visible outside its module to the rest of the crate, `pub(self)` is private
+fn is_public(vis: &syn::Visibility) -> bool {
+    match vis {
+        syn::Visibility::Public(_) => true,
+        syn::Visibility::Restricted(vis) => !vis.path.is_ident("self"),
+        syn::Visibility::Inherited => false,
+    }
+}
+
+fn item_attrs(item: &Item) -> &[Attribute] {
+    match item {
+        Item::Mod(item) => &item.attrs,
+        Item::Use(item) => &item.attrs,
+        Item::Struct(item) => &item.attrs,
+        Item::Impl(item) => &item.attrs,
+        Item::Const(item) => &item.attrs,
+        _ => &[],
+    }
+}
+
+/// Evaluate `#[cfg(...)]` attributes against the cfg cargo passes to build scripts,
+/// features against `features` when given.
+fn cfg_enabled(attrs: &[Attribute], features: Option<&[String]>) -> bool {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("cfg"))
+        .all(|attr| {
+            let meta: syn::Meta = attr
+                .parse_args()
+                .unwrap_or_else(|_| panic!("failed parse cfg '{}'", quote!(#attr)));
+            eval_cfg(&meta, features)
+        })
+}
+
+fn eval_cfg(meta: &syn::Meta, features: Option<&[String]>) -> bool {
+    let name = meta
+        .path()
+        .get_ident()
+        .map(|ident| ident.to_string())
+        .unwrap_or_default();
+    match meta {
+        syn::Meta::List(list) => {
+            let predicates = list
+                .parse_args_with(
+                    syn::punctuated::Punctuated::<syn::Meta, token::Comma>::parse_terminated,
+                )
+                .unwrap_or_else(|_| panic!("failed parse cfg predicate '{}'", name));
+            match name.as_str() {
+                "all" => predicates.iter().all(|meta| eval_cfg(meta, features)),
+                "any" => predicates.iter().any(|meta| eval_cfg(meta, features)),
+                "not" => !predicates.iter().all(|meta| eval_cfg(meta, features)),
+                _ => false,
+            }
+        }
+        syn::Meta::NameValue(name_value) => {
+            let syn::Expr::Lit(syn::ExprLit {
+                lit: syn::Lit::Str(value),
+                ..
+            }) = &name_value.value
+            else {
+                return false;
+            };
+            if name == "feature" {
+                if let Some(features) = features {
+                    return features.contains(&value.value());
+                }
+                let feature = value.value().to_uppercase().replace('-', "_");
+                return env::var_os(format!("CARGO_FEATURE_{}", feature)).is_some();
+            }
+            env::var(format!("CARGO_CFG_{}", name.to_uppercase()))
+                .is_ok_and(|values| values.split(',').any(|v| v == value.value()))
+        }
+        // `test` and `doc` are never set for build scripts
+        syn::Meta::Path(_) => env::var_os(format!("CARGO_CFG_{}", name.to_uppercase())).is_some(),
+    }
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 struct Metadata {
     config: Option<String>,
     export: bool,
     rename: Option<String>,
+    reload: bool,
+    profile: Option<String>,
+    context: Option<String>,
+    /// generated constructor, `new` unless renamed
+    constructor: Option<String>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct Provider {
     struct_type: String,
     ident: String,
     metadata: Metadata,
     injects: Vec<Inject>,
+    fields: Vec<String>,
+    module: Vec<String>,
+    source: Option<Source>,
+    /// type parameters of a generic struct, instantiated per injected type
+    generics: Vec<String>,
+}
+
+/// Where a provider is declared
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Source {
+    pub file: String,
+    pub line: usize,
 }
 
 impl Provider {
     pub(crate) fn new(struct_type: String, ident: String) -> Self {
-        return Self {
+        Self {
             struct_type,
             ident,
             metadata: Metadata::default(),
             injects: Vec::new(),
-        };
+            fields: Vec::new(),
+            module: Vec::new(),
+            source: None,
+            generics: Vec::new(),
+        }
     }
-    fn parse_attr(&mut self, attr: &Attribute) {
-        let _ = attr.parse_nested_meta(|meta| {
-            if meta.path.is_ident("config") {
-                if meta.input.peek(token::Paren) {
-                    let content;
-                    parenthesized!(content in meta.input);
-                    let lit: syn::LitStr = content.parse().expect(&format!(
-                        "failed parse attr 'config' content in provider '{}'",
-                        self.struct_type
-                    ));
-                    self.metadata.config = Some(lit.value());
-                } else {
-                    self.metadata.config = Some(self.ident.to_snake_case());
-                }
-            }
-            if meta.path.is_ident("export") {
-                self.metadata.export = true;
-            }
-            if meta.path.is_ident("rename") {
-                if meta.input.peek(token::Paren) {
-                    let content;
-                    parenthesized!(content in meta.input);
-                    let lit: syn::LitStr = content.parse().expect(&format!(
-                        "failed parse attr 'rename' content in provider '{}'",
-                        self.struct_type
-                    ));
-                    self.metadata.rename = Some(lit.value());
-                }
-            }
 
-            Ok(())
-        });
+    /// Name a `#[provider] const` is injected by, `rename` or the snake cased ident
+    fn value_name(&self) -> String {
+        self.metadata
+            .rename
+            .clone()
+            .unwrap_or_else(|| self.ident.to_snake_case())
+    }
+
+    /// Concrete provider of a generic struct for `args`, named after them so
+    /// `Repo<User>` and `Repo<Order>` get distinct variables.
+    fn instantiate(&self, args: &[String]) -> Provider {
+        if args.len() != self.generics.len() {
+            panic!(
+                "'{}' takes {} type arguments but {} were given",
+                self.struct_type,
+                self.generics.len(),
+                args.len()
+            )
+        }
+        let mut instance = self.clone();
+        instance.struct_type = format!("{}<{}>", self.struct_type, args.join(", "));
+        instance.ident = instance_ident(&self.ident, args, 1);
+        instance.generics.clear();
+        for inject in instance.injects.iter_mut() {
+            let ty: Type = parse_str(&inject.struct_type).unwrap();
+            inject.struct_type = map_type(&ty, true, &mut |path, _| match self
+                .generics
+                .iter()
+                .position(|param| *param == path)
+            {
+                Some(i) => args[i].clone(),
+                None => path,
+            });
+        }
+        instance
+    }
+
+    fn parse_attr(&mut self, attr: &Attribute) -> syn::Result<()> {
+        self.apply_args(ProviderArgs::from_attr(attr)?);
+        Ok(())
+    }
+
+    fn apply_args(&mut self, args: ProviderArgs) {
+        if let Some(section) = args.config {
+            self.metadata.config = Some(
+                section
+                    .map(|lit| lit.value())
+                    .unwrap_or_else(|| self.ident.to_snake_case()),
+            );
+        }
+        if let Some(context) = args.export {
+            self.metadata.export = true;
+            self.metadata.context = context.map(|lit| lit.value());
+        }
+        self.metadata.reload |= args.reload.is_some();
+        if let Some(profile) = args.profile {
+            self.metadata.profile = Some(profile.value());
+        }
+        if let Some(rename) = args.rename {
+            self.metadata.rename = Some(rename.value());
+        }
     }
 }
 
+/// Items of a module, paths inside providers are kept as written until
+/// `Resolver` resolves them against every module of the crate.
+#[derive(Clone, Serialize, Deserialize)]
 struct ModuleContext {
     mods: Vec<String>,
+    file: String,
+    decls: Vec<ModDecl>,
+    items: HashSet<String>,
     uses: HashMap<String, Vec<String>>,
+    globs: Vec<Vec<String>>,
+    /// items and `use` names visible outside the module
+    exports: HashSet<String>,
+    /// `pub use path::*`
+    export_globs: Vec<Vec<String>>,
     providers: HashMap<String, Provider>,
     injectors: Vec<Provider>,
-    implements: HashMap<String, Vec<String>>,
+    implements: HashMap<String, Vec<Provider>>,
+    values: Vec<Provider>,
 }
 
 impl ModuleContext {
-    fn new(mods: Vec<String>) -> ModuleContext {
+    fn new(mods: Vec<String>, file: String) -> ModuleContext {
         Self {
             mods,
+            file,
+            decls: Vec::new(),
+            items: HashSet::new(),
             uses: HashMap::new(),
+            globs: Vec::new(),
+            exports: HashSet::new(),
+            export_globs: Vec::new(),
             providers: HashMap::new(),
             injectors: Vec::new(),
             implements: HashMap::new(),
+            values: Vec::new(),
         }
     }
     fn module_path(&self) -> String {
-        return self.mods.join("::");
+        self.mods.join("::")
     }
 
-    fn abs_struct_or_trait_type(&self, ident: String) -> String {
-        format!("{}::{}", self.module_path(), ident)
+    fn source(&self, span: proc_macro2::Span) -> Source {
+        Source {
+            file: self.file.clone(),
+            line: span.start().line,
+        }
     }
 
-    fn resolve_abs_path_type(&self, trait_path: &Path) -> String {
-        let segments: Vec<String> = trait_path
-            .segments
-            .iter()
-            .map(|seg| seg.ident.to_string())
-            .collect();
-
-        let trait_type = segments.join("::");
-        if is_absolute_path(&segments) {
-            return trait_type;
-        }
+    /// Stop at invalid attribute arguments, a provider built without them would
+    /// generate a different graph than the macros expand for.
+    fn attr_error(&self, err: syn::Error) -> ! {
+        let start = err.span().start();
+        panic!("{}:{}:{}: {}", self.file, start.line, start.column + 1, err)
+    }
 
-        let first_segment = segments.first().unwrap();
-        let prefix = if let Some(absolute_path) = self.uses.get(first_segment) {
-            // replace use alias and concat absolute type path
-            Some(absolute_path[..absolute_path.len() - 1].join("::"))
-        } else if segments.len() > 1 {
-            // more then one segment, as abs path
-            None
-        } else {
-            // default in current module
-            Some(self.module_path())
-        };
-        if let Some(prefix) = prefix {
-            format!("{}::{}", prefix, trait_type)
-        } else {
-            trait_type
-        }
+    fn abs_struct_or_trait_type(&self, ident: String) -> String {
+        format!("{}::{}", self.module_path(), ident)
     }
 
     fn parse_inject_field_type(&self, mut inject: Inject, field_type: &Type) -> Option<Inject> {
@@ -476,7 +2074,7 @@ impl ModuleContext {
                 // parse last segment type
                 let segment = type_path.path.segments.last().unwrap();
                 if segment.ident == "Arc" {
-                    inject.wrapper_type = Some(self.resolve_abs_path_type(&type_path.path));
+                    inject.wrapper_type = Some(path_string(&type_path.path));
                     if let syn::PathArguments::AngleBracketed(args) = &segment.arguments {
                         if let Some(syn::GenericArgument::Type(inner_type)) = args.args.first() {
                             return self.parse_inject_field_type(inject, inner_type);
@@ -484,13 +2082,13 @@ impl ModuleContext {
                     }
                 }
 
-                inject.struct_type = self.resolve_abs_path_type(&type_path.path);
+                inject.struct_type = type_string(field_type);
                 Some(inject)
             }
             Type::TraitObject(trait_obj) => {
                 // parse first TraitBound
                 if let Some(syn::TypeParamBound::Trait(trait_bound)) = trait_obj.bounds.first() {
-                    inject.struct_type = self.resolve_abs_path_type(&trait_bound.path);
+                    inject.struct_type = path_type_string(&trait_bound.path);
                     inject.trait_object = true;
                     Some(inject)
                 } else {
@@ -506,24 +2104,52 @@ impl ModuleContext {
         let struct_path = self.abs_struct_or_trait_type(item.ident.to_string());
 
         let mut provider = Provider::new(struct_path, item.ident.to_string());
+        provider.module = self.mods.clone();
+        provider.source = Some(self.source(item.ident.span()));
+        // the constructor is named by `#[injectable]`, also for the `#[provider]` entry
+        if let Some(attr) = get_attr(&item.attrs, "injectable") {
+            let args = ProviderArgs::from_attr(&attr).unwrap_or_else(|err| self.attr_error(err));
+            provider.metadata.constructor = args.constructor.map(|ident| ident.to_string());
+        }
+        provider.generics = item
+            .generics
+            .type_params()
+            .map(|param| param.ident.to_string())
+            .collect();
 
         // parse attribute
         if let Some(attr) = attr {
-            provider.parse_attr(&attr);
+            provider
+                .parse_attr(&attr)
+                .unwrap_or_else(|err| self.attr_error(err));
         }
 
         // parse struct injector fields
         provider.injects = item
             .fields
             .iter()
-            .filter_map(|field| {
+            .enumerate()
+            .filter_map(|(index, field)| {
                 if let Some(attr) = get_attr(&field.attrs, "inject") {
                     // parse struct field type
                     // support field type:
                     // 1. Trait Object: dyn Bound, Box<dyn Trait>
                     // 2. Struct
                     let mut inject = Inject::default();
-                    inject.parse_attr(&attr);
+                    inject
+                        .parse_attr(&attr)
+                        .unwrap_or_else(|err| self.attr_error(err));
+                    // tuple struct fields are named by index
+                    inject.field = field
+                        .ident
+                        .as_ref()
+                        .map(|ident| ident.to_string())
+                        .unwrap_or_else(|| index.to_string());
+                    // values are injected as declared, `&'static str` included
+                    if inject.value.is_some() {
+                        inject.struct_type = type_string(&field.ty);
+                        return Some(inject);
+                    }
                     self.parse_inject_field_type(inject, &field.ty)
                 } else {
                     None
@@ -531,23 +2157,78 @@ impl ModuleContext {
             })
             .collect();
 
+        if provider.metadata.config.is_some() {
+            provider.fields = config_fields(item);
+        }
+
         provider
     }
 
     fn parse_item_use(&mut self, item_use: ItemUse) {
-        let items = parse_use_tree(&item_use.tree, Vec::new());
+        let prefix = if item_use.leading_colon.is_some() {
+            vec![String::new()]
+        } else {
+            Vec::new()
+        };
+        let public = is_public(&item_use.vis);
+        let items = parse_use_tree(&item_use.tree, prefix);
         for (ident, prefix) in items {
-            self.uses.insert(ident, prefix);
+            if ident == "*" {
+                if public {
+                    self.export_globs.push(prefix.clone());
+                }
+                self.globs.push(prefix);
+            } else if ident != "_" {
+                if public {
+                    self.exports.insert(ident.clone());
+                }
+                self.uses.insert(ident, prefix);
+            }
         }
     }
-    fn parse_item_mod(&mut self, item_mod: ItemMod) -> Vec<ModuleContext> {
-        if item_mod.content.is_none() {
-            return vec![];
-        }
-        let mut clone_mods: Vec<_> = self.mods.iter().cloned().collect();
+    fn parse_item_mod(
+        &mut self,
+        item_mod: ItemMod,
+        dir: &ModuleDir,
+        wire: bool,
+        features: Option<&[String]>,
+    ) -> Vec<ModuleContext> {
+        let mut clone_mods = self.mods.to_vec();
         clone_mods.push(item_mod.ident.to_string());
-        let (_, items) = item_mod.content.unwrap();
-        parse_module(clone_mods, items)
+        let name = item_mod.ident.unraw().to_string();
+        let path = get_attr(&item_mod.attrs, "path").map(|attr| {
+            let syn::Meta::NameValue(syn::MetaNameValue {
+                value:
+                    syn::Expr::Lit(syn::ExprLit {
+                        lit: syn::Lit::Str(lit),
+                        ..
+                    }),
+                ..
+            }) = attr.meta
+            else {
+                panic!("failed parse attr 'path' in mod '{}'", name)
+            };
+            dir.file_dir.join(lit.value())
+        });
+
+        // inline module, nested modules resolve in a directory named after it
+        if let Some((_, items)) = item_mod.content {
+            let child = path.unwrap_or_else(|| dir.dir.join(&name));
+            let dir = ModuleDir {
+                dir: child.clone(),
+                file_dir: child,
+                file: dir.file.clone(),
+            };
+            return parse_module(clone_mods, items, dir, wire, features);
+        }
+
+        self.decls.push(ModDecl {
+            mods: clone_mods,
+            name,
+            path,
+            dir: dir.dir.clone(),
+        });
+        Vec::new()
     }
 
     fn parse_item_struct(&mut self, item_struct: ItemStruct) {
@@ -561,24 +2242,338 @@ impl ModuleContext {
             self.providers
                 .insert(provider.struct_type.clone(), provider);
         }
+
+        // `#[config("section")]` is `#[provider(config("section"))]`
+        if let Some(attr) = get_attr(&item_struct.attrs, "config") {
+            let mut provider = self.parse_provider(&item_struct, None);
+            let args = ConfigArgs::from_attr(&attr).unwrap_or_else(|err| self.attr_error(err));
+            provider.apply_args(args);
+            provider.fields = config_fields(&item_struct);
+            self.providers
+                .insert(provider.struct_type.clone(), provider);
+        }
+    }
+    /// `#[provider] const` values, injected by name with `#[inject(value = "name")]`
+    fn parse_item_const(&mut self, item_const: syn::ItemConst) {
+        let Some(attr) = get_attr(&item_const.attrs, "provider") else {
+            return;
+        };
+        let ident = item_const.ident.to_string();
+        let mut value = Provider::new(self.abs_struct_or_trait_type(ident.clone()), ident);
+        value.module = self.mods.clone();
+        value.source = Some(self.source(item_const.ident.span()));
+        value
+            .parse_attr(&attr)
+            .unwrap_or_else(|err| self.attr_error(err));
+        self.values.push(value);
     }
     fn parse_item_impl(&mut self, item_impl: ItemImpl) {
-        if !has_attr(&item_impl.attrs, "provider") {
+        let Some(attr) = get_attr(&item_impl.attrs, "provider") else {
             return;
-        }
+        };
         if let Some((_, trait_path, _)) = &item_impl.trait_ {
-            let abs_trait_type = self.resolve_abs_path_type(trait_path);
+            let abs_trait_type = path_type_string(trait_path);
 
             if let Type::Path(type_path) = item_impl.self_ty.as_ref() {
-                let asb_struct_path = self.resolve_abs_path_type(&type_path.path);
+                let asb_struct_path = type_string(&item_impl.self_ty);
+                let ident = type_path.path.segments.last().unwrap().ident.to_string();
+                let mut implement = Provider::new(asb_struct_path, ident);
+                implement.module = self.mods.clone();
+                implement.source = Some(self.source(item_impl.impl_token.span));
+                implement
+                    .parse_attr(&attr)
+                    .unwrap_or_else(|err| self.attr_error(err));
                 if let Some(structs) = self.implements.get_mut(&abs_trait_type) {
-                    structs.push(asb_struct_path);
+                    structs.push(implement);
+                } else {
+                    self.implements.insert(abs_trait_type, vec![implement]);
+                }
+            }
+        }
+    }
+}
+
+/// Module scopes of the crate, resolves paths as written inside a module to the
+/// path of the item definition, following `use` renames, globs and re-exports.
+struct Resolver {
+    scopes: HashMap<String, Scope>,
+    /// finished lookups, in-progress ones are `None` so import cycles find nothing
+    lookups: RefCell<HashMap<LookupKey, Option<Vec<String>>>>,
+}
+
+/// module, name and the module importing it through a glob
+type LookupKey = (String, String, Option<String>);
+
+#[derive(Clone, Serialize, Deserialize)]
+struct Scope {
+    items: HashSet<String>,
+    uses: HashMap<String, Vec<String>>,
+    globs: Vec<Vec<String>>,
+    exports: HashSet<String>,
+    export_globs: Vec<Vec<String>>,
+}
+
+impl Resolver {
+    fn new(modules: &[ModuleContext]) -> Self {
+        let scopes = modules
+            .iter()
+            .map(|module| {
+                (
+                    module.module_path(),
+                    Scope {
+                        items: module.items.clone(),
+                        uses: module.uses.clone(),
+                        globs: module.globs.clone(),
+                        exports: module.exports.clone(),
+                        export_globs: module.export_globs.clone(),
+                    },
+                )
+            })
+            .collect();
+        Self {
+            scopes,
+            lookups: RefCell::new(HashMap::new()),
+        }
+    }
+
+    fn resolve_provider(&self, provider: &mut Provider) {
+        for inject in provider.injects.iter_mut().filter(|v| v.value.is_none()) {
+            // `wire::Watch<T>` injects the reloadable section `T`, matched by path so
+            // other types named `Watch` stay providers
+            let (path, args) = split_type(&inject.struct_type);
+            let path = self.resolve(&provider.module, &path);
+            if args.len() == 1 && (path == "wire::Watch" || path == "::wire::Watch") {
+                inject.watch = true;
+                inject.struct_type = args[0].clone();
+            }
+            inject.struct_type =
+                self.resolve_type(&provider.module, &inject.struct_type, &provider.generics);
+            if let Some(wrapper_type) = inject.wrapper_type.as_mut() {
+                *wrapper_type = self.resolve(&provider.module, wrapper_type);
+            }
+            if let Some(DefaultValue::Factory(factory)) = inject.default.as_mut() {
+                *factory = self.resolve(&provider.module, factory);
+            }
+        }
+    }
+
+    /// Resolve the paths of a type used in `module`, type arguments unknown to the
+    /// crate are kept as written like prelude types and `params` of the provider.
+    fn resolve_type(&self, module: &[String], ty: &str, params: &[String]) -> String {
+        let ty: Type = parse_str(ty).unwrap_or_else(|_| panic!("failed parse type '{}'", ty));
+        map_type(&ty, true, &mut |path, top| {
+            if params.contains(&path) {
+                path
+            } else if top {
+                self.resolve(module, &path)
+            } else {
+                let segments: Vec<String> = path.split("::").map(str::to_string).collect();
+                self.resolve_segments(module, &segments)
+                    .map(|segments| segments.join("::"))
+                    .unwrap_or(path)
+            }
+        })
+    }
+
+    /// Resolve a `::` separated path used in `module`, unknown single names default to
+    /// the module itself and other unknown paths are kept as extern paths.
+    fn resolve(&self, module: &[String], path: &str) -> String {
+        let segments: Vec<String> = path.split("::").map(str::to_string).collect();
+        let resolved = self.resolve_segments(module, &segments).unwrap_or_else(|| {
+            if segments.len() == 1 {
+                let mut resolved = module.to_vec();
+                resolved.extend(segments);
+                resolved
+            } else {
+                segments
+            }
+        });
+        resolved.join("::")
+    }
+
+    fn resolve_segments(&self, module: &[String], segments: &[String]) -> Option<Vec<String>> {
+        let (first, rest) = segments.split_first()?;
+        let mut current = match first.as_str() {
+            // `::name` is always an extern crate
+            "" => return Some(segments.to_vec()),
+            "crate" => vec![module.first()?.clone()],
+            "self" => module.to_vec(),
+            "super" => module[..module.len().saturating_sub(1)].to_vec(),
+            // local names shadow crates added by `Builder::add_crate`
+            name => self.lookup(module, name, None).or_else(|| {
+                self.scopes
+                    .contains_key(name)
+                    .then(|| vec![name.to_string()])
+            })?,
+        };
+        for segment in rest {
+            if segment == "super" {
+                current.pop();
+                continue;
+            }
+            current = if self.scopes.contains_key(&current.join("::")) {
+                self.lookup(&current, segment, None)
+            } else {
+                None
+            }
+            .unwrap_or_else(|| {
+                let mut next = current.clone();
+                next.push(segment.clone());
+                next
+            });
+        }
+        Some(current)
+    }
+
+    /// Shortest path of each item re-exported by a shorter or public path than its
+    /// definition. The building crate is seen from the generated module, a child of
+    /// its root, dependency crates from outside through `pub` names only.
+    fn visible_paths(&self) -> HashMap<String, String> {
+        let mut roots: Vec<&String> = self
+            .scopes
+            .keys()
+            .filter(|module| !module.contains("::"))
+            .collect();
+        roots.sort();
+        let mut queue: VecDeque<_> = roots
+            .into_iter()
+            .map(|root| {
+                let viewer = if root == "crate" {
+                    vec![root.clone(), String::new()]
                 } else {
-                    self.implements
-                        .insert(abs_trait_type, vec![asb_struct_path]);
+                    Vec::new()
+                };
+                (vec![root.clone()], vec![root.clone()], viewer)
+            })
+            .collect();
+
+        // breadth first, the first path found to an item is a shortest one
+        let mut visited = HashSet::new();
+        let mut paths = HashMap::new();
+        while let Some((module, path, viewer)) = queue.pop_front() {
+            if !visited.insert(module.join("::")) {
+                continue;
+            }
+            let mut names: Vec<_> = self
+                .visible_names(&module, &viewer, &mut HashSet::new())
+                .into_iter()
+                .collect();
+            names.sort();
+            for name in names {
+                let Some(item) = self.lookup(&module, &name, Some(&viewer)) else {
+                    continue;
+                };
+                // extern items keep their own path
+                if !self.scopes.contains_key(&item[0]) {
+                    continue;
+                }
+                let mut item_path = path.clone();
+                item_path.push(name);
+                if self.scopes.contains_key(&item.join("::")) {
+                    queue.push_back((item.clone(), item_path.clone(), viewer.clone()));
                 }
+                paths
+                    .entry(item.join("::"))
+                    .or_insert_with(|| item_path.join("::"));
+            }
+        }
+        paths.retain(|item, path| item != path);
+        paths
+    }
+
+    /// Names of a module visible to `viewer`, glob imports included
+    fn visible_names(
+        &self,
+        module: &[String],
+        viewer: &[String],
+        visited: &mut HashSet<String>,
+    ) -> HashSet<String> {
+        let mut names = HashSet::new();
+        let Some(scope) = self.scopes.get(&module.join("::")) else {
+            return names;
+        };
+        if !visited.insert(module.join("::")) {
+            return names;
+        }
+        let private = viewer.starts_with(module);
+        names.extend(
+            scope
+                .items
+                .iter()
+                .chain(scope.uses.keys())
+                .filter(|name| private || scope.exports.contains(*name))
+                .cloned(),
+        );
+        for glob in scope
+            .globs
+            .iter()
+            .filter(|glob| private || scope.export_globs.contains(glob))
+        {
+            if let Some(target) = self.resolve_segments(module, glob) {
+                names.extend(self.visible_names(&target, viewer, visited));
             }
         }
+        names
+    }
+
+    /// Find a name in a module scope, `from` is the module importing it through a
+    /// glob. Each lookup is done once, which bounds glob imports of each other.
+    fn lookup(
+        &self,
+        module: &[String],
+        name: &str,
+        from: Option<&[String]>,
+    ) -> Option<Vec<String>> {
+        let key = (
+            module.join("::"),
+            name.to_string(),
+            from.map(|from| from.join("::")),
+        );
+        if let Some(found) = self.lookups.borrow().get(&key) {
+            return found.clone();
+        }
+        self.lookups.borrow_mut().insert(key.clone(), None);
+        let found = self.lookup_scope(module, name, from);
+        self.lookups.borrow_mut().insert(key, found.clone());
+        found
+    }
+
+    /// Items defined in the module first, then `use` declarations and at last glob
+    /// imports. A glob imports the names visible to `from`, private ones only into
+    /// the module itself and its children.
+    fn lookup_scope(
+        &self,
+        module: &[String],
+        name: &str,
+        from: Option<&[String]>,
+    ) -> Option<Vec<String>> {
+        let scope = self.scopes.get(&module.join("::"))?;
+        let private = from.is_none_or(|from| from.starts_with(module));
+        let defined = scope.items.contains(name) || scope.uses.contains_key(name);
+        if defined && !private && !scope.exports.contains(name) {
+            // a private name shadows glob imports of the module too
+            return None;
+        }
+        if scope.items.contains(name) {
+            let mut path = module.to_vec();
+            path.push(name.to_string());
+            return Some(path);
+        }
+        if let Some(path) = scope.uses.get(name) {
+            // use paths start at the module or an extern crate
+            return self
+                .resolve_segments(module, path)
+                .or_else(|| Some(path.clone()));
+        }
+        let importer = from.unwrap_or(module);
+        scope
+            .globs
+            .iter()
+            .filter(|glob| private || scope.export_globs.contains(glob))
+            .find_map(|glob| {
+                let target = self.resolve_segments(module, glob)?;
+                self.lookup(&target, name, Some(importer))
+            })
     }
 }
 
@@ -594,6 +2589,11 @@ fn parse_use_tree(tree: &UseTree, mut prefix: Vec<String>) -> Vec<(String, Vec<S
             .iter()
             .flat_map(|tree| parse_use_tree(tree, prefix.clone()))
             .collect::<Vec<_>>(),
+        // `use foo::{self}` imports the module itself
+        UseTree::Name(name) if name.ident == "self" => match prefix.last() {
+            Some(ident) => vec![(ident.clone(), prefix)],
+            None => vec![],
+        },
         UseTree::Name(name) => {
             let ident = name.ident.to_string();
             prefix.push(ident.clone());
@@ -602,35 +2602,27 @@ fn parse_use_tree(tree: &UseTree, mut prefix: Vec<String>) -> Vec<(String, Vec<S
         UseTree::Rename(rename) => {
             let from = rename.ident.to_string();
             let to = rename.rename.to_string();
-            prefix.push(from);
+            if from != "self" {
+                prefix.push(from);
+            }
             vec![(to, prefix)]
         }
-
-        _ => {
-            vec![]
-        }
+        // glob import, marked by `*`
+        UseTree::Glob(_) => vec![("*".to_string(), prefix)],
     }
 }
 
-fn parse_file_path(path: &std::path::Path) -> Vec<String> {
-    if !path.starts_with("src") {
-        return vec![];
-    }
-    let path_buf = path.with_extension("");
-    let stem = path_buf.to_str().unwrap();
-
-    stem.split('/')
-        .filter_map(|v| match v {
-            "src" => Some("crate".to_string()),
-            "mod" => None,
-            "lib" => None,
-            seg => Some(seg.to_string()),
+/// Config fields visible to the generated module, they can be overridden by key
+fn config_fields(item: &ItemStruct) -> Vec<String> {
+    item.fields
+        .iter()
+        .filter(|field| match &field.vis {
+            syn::Visibility::Public(_) => true,
+            syn::Visibility::Restricted(vis) => vis.path.is_ident("crate"),
+            syn::Visibility::Inherited => false,
         })
-        .collect::<Vec<_>>()
-}
-
-fn has_attr(attrs: &[syn::Attribute], name: &str) -> bool {
-    attrs.iter().any(|attr| attr.path().is_ident(name))
+        .filter_map(|field| field.ident.as_ref().map(|ident| ident.to_string()))
+        .collect()
 }
 
 fn get_attr(attrs: &[syn::Attribute], name: &str) -> Option<syn::Attribute> {
@@ -646,12 +2638,97 @@ fn build_ident(name: &str) -> proc_macro2::Ident {
     syn::Ident::new(name, proc_macro2::Span::call_site())
 }
 
-fn is_absolute_path(segments: &Vec<String>) -> bool {
-    if let Some(seg) = segments.first() {
-        seg == "crate" || seg == ""
-    } else {
-        false
+fn build_config_path(name: &str) -> Vec<proc_macro2::Ident> {
+    let mut parts = vec!["config"];
+    parts.extend(name.split('.'));
+    parts.into_iter().map(build_ident).collect()
+}
+
+fn build_watch_ident(name: &str) -> proc_macro2::Ident {
+    build_ident(format!("{}_watch", name.replace('.', "_")).as_str())
+}
+
+fn path_string(path: &Path) -> String {
+    let mut segments = Vec::new();
+    if path.leading_colon.is_some() {
+        segments.push(String::new());
+    }
+    segments.extend(path.segments.iter().map(|seg| seg.ident.to_string()));
+    segments.join("::")
+}
+
+/// Canonical string of a type, `Repo<User>`, graph nodes are keyed by it.
+fn type_string(ty: &Type) -> String {
+    map_type(ty, true, &mut |path, _| path)
+}
+
+fn path_type_string(path: &Path) -> String {
+    type_string(&Type::Path(syn::TypePath {
+        qself: None,
+        path: path.clone(),
+    }))
+}
+
+/// Build the canonical string of `ty` passing the path of it and of each type
+/// argument through `map`, called with `true` for the outer path.
+fn map_type(ty: &Type, top: bool, map: &mut dyn FnMut(String, bool) -> String) -> String {
+    let Type::Path(type_path) = ty else {
+        return quote!(#ty).to_string();
+    };
+    if type_path.qself.is_some() {
+        return quote!(#ty).to_string();
+    }
+    let mut mapped = map(path_string(&type_path.path), top);
+    let arguments = &type_path.path.segments.last().unwrap().arguments;
+    if let syn::PathArguments::AngleBracketed(args) = arguments {
+        let args: Vec<String> = args
+            .args
+            .iter()
+            .map(|arg| match arg {
+                syn::GenericArgument::Type(ty) => map_type(ty, false, map),
+                arg => quote!(#arg).to_string(),
+            })
+            .collect();
+        mapped.push_str(&format!("<{}>", args.join(", ")));
     }
+    mapped
+}
+
+/// Split `Repo<User>` into the struct path and its type arguments.
+fn split_type(ty: &str) -> (String, Vec<String>) {
+    let Ok(Type::Path(type_path)) = parse_str::<Type>(ty) else {
+        return (ty.to_string(), Vec::new());
+    };
+    let args = match &type_path.path.segments.last().unwrap().arguments {
+        syn::PathArguments::AngleBracketed(args) => args
+            .args
+            .iter()
+            .filter_map(|arg| match arg {
+                syn::GenericArgument::Type(ty) => Some(type_string(ty)),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+    (path_string(&type_path.path), args)
+}
+
+/// Members of `Dependency` and `DependencyBuilder` besides the manual dependencies
+const DEPENDENCY_MEMBERS: &[&str] = &["config", "build", "builder"];
+
+/// Ident of a generic struct's instantiation, the generic ident followed by the
+/// last `level` segments of each type argument path, `Repo<a::User>` is `RepoUser`
+/// at level 1 and `RepoAUser` at level 2.
+fn instance_ident(ident: &str, args: &[String], level: usize) -> String {
+    args.iter().fold(ident.to_string(), |ident, arg| {
+        let ty: Type = parse_str(arg).unwrap();
+        ident
+            + &map_type(&ty, true, &mut |path, _| {
+                let segments: Vec<&str> = path.split("::").filter(|seg| *seg != "crate").collect();
+                segments[segments.len().saturating_sub(level)..].join("_")
+            })
+            .to_upper_camel_case()
+    })
 }
 
 struct Variant {
@@ -661,69 +2738,429 @@ struct Variant {
 
 struct Dep {
     ident: proc_macro2::Ident,
+    name: Option<String>,
     path: syn::Path,
     trait_object: bool,
     wrapper_type: Option<String>,
+    default: Option<DefaultValue>,
 }
 
 impl Dep {
+    /// explicit field name and field type, identifies a `Dependency` field
+    fn key(&self) -> (Option<String>, String) {
+        let field_type = self.build_type().to_string();
+        let field_type = field_type
+            .replace(" :: ", "::")
+            .replace(" < ", "<")
+            .replace(" >", ">");
+        (self.name.clone(), field_type)
+    }
+    /// snake cased type path segments without the crate root
+    fn module_segments(&self) -> Vec<String> {
+        self.path
+            .segments
+            .iter()
+            .skip_while(|seg| seg.ident == "crate")
+            .map(|seg| seg.ident.to_string().to_snake_case())
+            .collect()
+    }
     fn build_param(&self, dep: &proc_macro2::Ident) -> TokenStream {
         let ident = &self.ident;
         quote! {#dep.#ident.clone()}
     }
-    fn build_field(&self) -> TokenStream {
-        let ident = &self.ident;
+    fn build_value(&self, value: TokenStream) -> TokenStream {
+        match self.default.as_ref() {
+            Some(DefaultValue::Default) => quote! {#value.unwrap_or_default()},
+            Some(DefaultValue::Factory(factory)) => {
+                let factory: syn::Path = syn::parse_str(factory).unwrap();
+                quote! {#value.unwrap_or_else(#factory)}
+            }
+            None => quote! {#value.unwrap()},
+        }
+    }
+    fn build_type(&self) -> TokenStream {
         let path = &self.path;
         if let Some(v) = self.wrapper_type.as_ref() {
             let wrapper_type: syn::Path = syn::parse_str(v).unwrap();
             if self.trait_object {
-                quote! {pub #ident: #wrapper_type<dyn #path>}
+                quote! {#wrapper_type<dyn #path>}
             } else {
-                quote! {pub #ident: #wrapper_type<#path>}
+                quote! {#wrapper_type<#path>}
             }
         } else {
-            quote! {pub #ident: #path}
+            quote! {#path}
         }
     }
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 struct Inject {
+    field: String,
     trait_object: bool,
     wrapper_type: Option<String>,
+    watch: bool,
     struct_type: String,
     manual: bool,
+    default: Option<DefaultValue>,
+    name: Option<String>,
+    /// `#[inject(value = "...")]`, a `#[provider] const` name or a `section.field`
+    /// config key
+    value: Option<String>,
 }
 
-impl Inject {
-    fn parse_attr(&mut self, attr: &syn::Attribute) {
-        let _ = attr.parse_nested_meta(|meta| {
-            if meta.path.is_ident("manual") {
-                self.manual = true
-            }
+/// Fallback of an optional manual dependency
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+enum DefaultValue {
+    /// `#[inject(manual, default)]`, the type's `Default`
+    Default,
+    /// `#[inject(manual, default = "path::fn")]`, absolute factory path
+    Factory(String),
+}
 
-            Ok(())
+impl Inject {
+    fn parse_attr(&mut self, attr: &syn::Attribute) -> syn::Result<()> {
+        let args = InjectArgs::from_attr(attr)?;
+        self.manual = args.manual;
+        self.default = args.default.map(|factory| match factory {
+            Some(lit) => DefaultValue::Factory(lit.value()),
+            None => DefaultValue::Default,
         });
+        self.name = args.name.map(|lit| lit.value());
+        self.value = args.value.map(|lit| lit.value());
+        Ok(())
     }
 
     fn build_dep(&self) -> Dep {
         let path: syn::Path = syn::parse_str(&self.struct_type)
-            .expect(&format!("parse struct type failed, {}", self.struct_type));
-        let ident = build_ident(
-            path.segments
-                .last()
-                .as_ref()
-                .unwrap()
-                .ident
-                .to_string()
-                .to_snake_case()
-                .as_str(),
-        );
+            .unwrap_or_else(|_| panic!("parse struct type failed, {}", self.struct_type));
+        let ident = if let Some(name) = self.name.as_ref() {
+            build_ident(name)
+        } else {
+            build_ident(
+                path.segments
+                    .last()
+                    .as_ref()
+                    .unwrap()
+                    .ident
+                    .to_string()
+                    .to_snake_case()
+                    .as_str(),
+            )
+        };
         Dep {
             ident: ident.clone(),
+            name: self.name.clone(),
             path,
             trait_object: self.trait_object,
             wrapper_type: self.wrapper_type.clone(),
+            default: self.default.clone(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse_quote;
+
+    /// Held by tests reading or setting the cfg variables cargo passes build scripts
+    pub(crate) static ENV: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+    fn parse(source: &str) -> Vec<ModuleContext> {
+        parse_source(
+            vec!["crate".to_string()],
+            std::path::Path::new("src/lib.rs"),
+            true,
+            source,
+            None,
+        )
+    }
+
+    fn resolver(source: &str) -> Resolver {
+        Resolver::new(&parse(source))
+    }
+
+    fn module(path: &str) -> Vec<String> {
+        path.split("::").map(str::to_string).collect()
+    }
+
+    #[test]
+    fn resolves_glob_imports() {
+        let resolver = resolver(
+            "mod a { pub struct Repo; }
+             mod b { use crate::a::*; }",
+        );
+        assert_eq!(
+            resolver.resolve(&module("crate::b"), "Repo"),
+            "crate::a::Repo"
+        );
+    }
+
+    #[test]
+    fn resolves_renamed_re_exports() {
+        let resolver = resolver(
+            "mod a {
+                 mod inner { pub struct Repo; }
+                 pub use inner::Repo as Store;
+             }
+             mod b { use super::a; }",
+        );
+        assert_eq!(
+            resolver.resolve(&module("crate::b"), "a::Store"),
+            "crate::a::inner::Repo"
+        );
+    }
+
+    #[test]
+    fn glob_cycles_terminate() {
+        let resolver = resolver(
+            "pub mod a { pub use crate::b::*; pub struct A; }
+             pub mod b { pub use crate::a::*; pub struct B; }",
+        );
+        assert_eq!(resolver.resolve(&module("crate::a"), "B"), "crate::b::B");
+        assert_eq!(resolver.resolve(&module("crate::b"), "A"), "crate::a::A");
+        // unknown names default to the module they're used in
+        assert_eq!(
+            resolver.resolve(&module("crate::a"), "Missing"),
+            "crate::a::Missing"
+        );
+    }
+
+    #[test]
+    fn globs_import_only_visible_names() {
+        let resolver = resolver(
+            "mod a {
+                 mod inner { pub struct Public; struct Hidden; }
+                 pub use inner::*;
+             }
+             mod b { use crate::a::*; }",
+        );
+        assert_eq!(
+            resolver.resolve(&module("crate::b"), "Public"),
+            "crate::a::inner::Public"
+        );
+        assert_eq!(
+            resolver.resolve(&module("crate::b"), "Hidden"),
+            "crate::b::Hidden"
+        );
+    }
+
+    #[test]
+    fn names_re_exported_items_by_their_public_path() {
+        let resolver = resolver(
+            "pub mod a {
+                 mod inner { pub struct Repo; pub struct Other; }
+                 pub use inner::Repo;
+             }
+             pub struct Top;",
+        );
+        let paths = resolver.visible_paths();
+        assert_eq!(
+            paths.get("crate::a::inner::Repo").map(String::as_str),
+            Some("crate::a::Repo")
+        );
+        // reachable by their definition path or not at all
+        assert!(!paths.contains_key("crate::Top"));
+        assert!(!paths.contains_key("crate::a::inner::Other"));
+    }
+
+    #[test]
+    fn skeleton_keeps_names_and_drops_bodies() {
+        let tokens: TokenStream = "
+            use crate::a::{B, C};
+            pub fn f() { let x = 1; }
+            pub struct S { field: u8 }
+            mod m { fn g() { g() } }
+            const X: u8 = { 1 };"
+            .parse()
+            .unwrap();
+        let expected: TokenStream = "
+            use crate::a::{B, C};
+            pub fn f() {}
+            pub struct S {}
+            mod m { fn g() {} }
+            const X: u8 = {};"
+            .parse()
+            .unwrap();
+        assert_eq!(skeleton(tokens).to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn pre_scans_wire_attributes() {
+        assert!(has_wire_attrs("#[provider]\nstruct S;"));
+        assert!(has_wire_attrs("#[injectable(export)]\nstruct S;"));
+        assert!(!has_wire_attrs("fn provider() {}\nstruct Config;"));
+    }
+
+    #[test]
+    fn evaluates_cfg_predicates() {
+        let _env = ENV.lock().unwrap_or_else(|err| err.into_inner());
+        env::set_var("CARGO_FEATURE_WIRE_CFG_ON", "1");
+        env::set_var("CARGO_CFG_WIRE_CFG_OS", "linux,unix");
+        let enabled = |attr: syn::Attribute| cfg_enabled(&[attr], None);
+
+        assert!(enabled(parse_quote!(#[cfg(feature = "wire-cfg-on")])));
+        assert!(!enabled(parse_quote!(#[cfg(feature = "wire-cfg-off")])));
+        assert!(enabled(parse_quote!(#[cfg(wire_cfg_os = "unix")])));
+        assert!(enabled(parse_quote!(#[cfg(all(
+            feature = "wire-cfg-on",
+            not(feature = "wire-cfg-off")
+        ))])));
+        assert!(enabled(
+            parse_quote!(#[cfg(any(feature = "wire-cfg-off", wire_cfg_os))])
+        ));
+        // never set for build scripts
+        assert!(!enabled(parse_quote!(#[cfg(test)])));
+
+        env::remove_var("CARGO_FEATURE_WIRE_CFG_ON");
+        env::remove_var("CARGO_CFG_WIRE_CFG_OS");
+    }
+
+    #[test]
+    fn skips_disabled_modules() {
+        let _env = ENV.lock().unwrap_or_else(|err| err.into_inner());
+        let modules = parse(
+            "#[cfg(feature = \"wire-cfg-off\")]
+             mod gone { pub struct Gone; }
+             #[cfg(not(feature = \"wire-cfg-off\"))]
+             mod kept { pub struct Kept; }",
+        );
+        let paths: Vec<String> = modules.iter().map(ModuleContext::module_path).collect();
+        assert!(paths.contains(&"crate::kept".to_string()));
+        assert!(!paths.contains(&"crate::gone".to_string()));
+    }
+
+    #[test]
+    fn evaluates_dependency_cfg_with_its_features() {
+        let _env = ENV.lock().unwrap_or_else(|err| err.into_inner());
+        env::set_var("CARGO_FEATURE_WIRE_CFG_HOST", "1");
+        let features = vec!["wire-cfg-dep".to_string()];
+        let modules = parse_source(
+            vec!["dep".to_string()],
+            std::path::Path::new("dep/src/lib.rs"),
+            true,
+            "#[cfg(feature = \"wire-cfg-dep\")]
+             mod own { pub struct Own; }
+             #[cfg(feature = \"wire-cfg-host\")]
+             mod host { pub struct Host; }",
+            Some(&features),
+        );
+        env::remove_var("CARGO_FEATURE_WIRE_CFG_HOST");
+        let paths: Vec<String> = modules.iter().map(ModuleContext::module_path).collect();
+        assert!(paths.contains(&"dep::own".to_string()));
+        assert!(!paths.contains(&"dep::host".to_string()));
+    }
+
+    #[test]
+    #[should_panic(expected = "src/lib.rs:2:14: unsupported provider argument")]
+    fn reports_invalid_provider_arguments_at_their_location() {
+        parse("#[provider]\n#[injectable(bogus)]\npub struct Repo {}");
+    }
+
+    #[test]
+    #[should_panic(expected = "src/lib.rs:3:22: unsupported inject argument")]
+    fn reports_invalid_inject_arguments_at_their_location() {
+        parse(
+            "#[injectable]\npub struct Repo {\n    #[inject(manual, nmae = \"x\")]\n    x: u8,\n}",
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "src/lib.rs:1:17: config takes only a section name")]
+    fn reports_invalid_config_arguments_at_their_location() {
+        parse("#[config(\"app\", export)]\npub struct App {}");
+    }
+
+    /// `Dependency` field names of the manual injects of `source`, by field type
+    fn dependency_names(source: &str) -> HashMap<String, String> {
+        let mut builder = configure();
+        builder.merge(resolve_modules(parse(source), HashMap::new()));
+        builder.resolve_dependency_names();
+        builder
+            .dependency_names
+            .iter()
+            .map(|((_, field_type), ident)| (field_type.clone(), ident.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn qualifies_colliding_dependency_names() {
+        let names = dependency_names(
+            "mod a { pub struct Connection; }
+             mod b { pub struct Connection; }
+             #[provider]
+             #[injectable]
+             pub struct Repo {
+                 #[inject(manual)]
+                 a: a::Connection,
+                 #[inject(manual)]
+                 b: b::Connection,
+             }",
+        );
+        assert_eq!(names["crate::a::Connection"], "a_connection");
+        assert_eq!(names["crate::b::Connection"], "b_connection");
+    }
+
+    #[test]
+    fn names_dependencies_by_their_name_override() {
+        let names = dependency_names(
+            "mod a { pub struct Connection; }
+             mod b { pub struct Connection; }
+             #[provider]
+             #[injectable]
+             pub struct Repo {
+                 #[inject(manual, name = \"primary\")]
+                 a: a::Connection,
+                 #[inject(manual)]
+                 b: b::Connection,
+             }",
+        );
+        assert_eq!(names["crate::a::Connection"], "primary");
+        assert_eq!(names["crate::b::Connection"], "connection");
+    }
+
+    #[test]
+    fn qualifies_dependency_names_taken_by_dependency() {
+        let names = dependency_names(
+            "mod db { pub struct Config; }
+             #[provider]
+             #[injectable]
+             pub struct Repo {
+                 #[inject(manual)]
+                 config: db::Config,
+             }",
+        );
+        assert_eq!(names["crate::db::Config"], "db_config");
+    }
+
+    #[test]
+    #[should_panic(expected = "manual dependency name 'builder' of 'crate::Builder' is taken")]
+    fn rejects_unqualifiable_dependency_names_taken_by_dependency() {
+        dependency_names(
+            "pub struct Builder;
+             #[provider]
+             #[injectable]
+             pub struct Repo {
+                 #[inject(manual)]
+                 builder: Builder,
+             }",
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "manual dependency name 'db' is shared by")]
+    fn rejects_dependency_names_shared_by_types() {
+        dependency_names(
+            "mod a { pub struct Connection; }
+             mod b { pub struct Connection; }
+             #[provider]
+             #[injectable]
+             pub struct Repo {
+                 #[inject(manual, name = \"db\")]
+                 a: a::Connection,
+                 #[inject(manual, name = \"db\")]
+                 b: b::Connection,
+             }",
+        );
+    }
+}
@@ -1,3 +1,143 @@
-pub fn main() {
-    wire_build::configure().build();
+use std::{env, fs, panic, path::Path, process};
+
+use wire_build::Builder;
+
+const USAGE: &str = "\
+usage: wire <command> [options]
+
+commands:
+    check              resolve the graph and report errors
+    graph              print the graph, DOT by default
+    explain <Type>     show how a type is constructed and who consumes it
+    gen <path>         write the generated code to <path>
+    set <name> <path>  write the provider set manifest of crate <name> to <path>
+
+options:
+    --dir <dir>              source directory, defaults to src
+    --profile <profile>      build the graph for a profile
+    --features <a,b>         features enabled when evaluating cfg
+    --crate <name>=<path>[:<a,b>]
+                             also scan a dependency crate, with its features
+    --set <path>             include a provider set manifest
+    --json                   print the graph as JSON
+    --mermaid                print the graph as Mermaid";
+
+fn main() {
+    // the builder stops at graph errors by panicking, which is how a build script
+    // reports them, the CLI prints the message alone
+    panic::set_hook(Box::new(|_| {}));
+    if let Err(err) = panic::catch_unwind(run) {
+        let message = err
+            .downcast_ref::<String>()
+            .map(String::as_str)
+            .or_else(|| err.downcast_ref::<&str>().copied())
+            .unwrap_or("unknown error");
+        eprintln!("error: {}", message);
+        process::exit(1);
+    }
+}
+
+fn run() {
+    let mut args = env::args().skip(1);
+    let command = args.next().unwrap_or_else(|| usage(""));
+
+    let mut builder = wire_build::configure().build_script(false);
+    let mut positional = Vec::new();
+    let mut format = "dot";
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--dir" => builder = builder.dir(value(&arg, args.next())),
+            "--profile" => builder = builder.profile(value(&arg, args.next())),
+            "--features" => {
+                // cfg evaluation reads the variables cargo sets for build scripts
+                for feature in value(&arg, args.next()).split(',') {
+                    let name = feature.trim().to_uppercase().replace('-', "_");
+                    env::set_var(format!("CARGO_FEATURE_{}", name), "1");
+                }
+            }
+            "--crate" => {
+                let spec = value(&arg, args.next());
+                let (name, path) = spec
+                    .split_once('=')
+                    .unwrap_or_else(|| usage("--crate expects <name>=<path>[:<a,b>]"));
+                // features after the last `:`, unless it is part of the path
+                let (path, features) = match path.rsplit_once(':') {
+                    Some((path, features)) if !features.contains(['/', '\\']) => (
+                        path,
+                        features
+                            .split(',')
+                            .map(|feature| feature.trim().to_string())
+                            .filter(|feature| !feature.is_empty())
+                            .collect(),
+                    ),
+                    _ => (path, Vec::new()),
+                };
+                builder = builder.add_crate(name.to_string(), path.to_string(), features);
+            }
+            "--set" => builder = builder.include_set(value(&arg, args.next())),
+            "--json" => format = "json",
+            "--mermaid" => format = "mermaid",
+            "-h" | "--help" => usage(""),
+            _ if arg.starts_with('-') => usage(&format!("unknown option '{}'", arg)),
+            _ => positional.push(arg),
+        }
+    }
+
+    match (command.as_str(), positional.as_slice()) {
+        ("check", []) => {
+            builder.check();
+            println!("ok");
+        }
+        ("graph", []) => {
+            let graph = builder.resolve_graph();
+            match format {
+                "json" => println!("{}", graph.to_json()),
+                "mermaid" => print!("{}", graph.to_mermaid()),
+                _ => print!("{}", graph.to_dot()),
+            }
+        }
+        ("explain", [name]) => match builder.resolve_graph().explain(name) {
+            Some(explained) => print!("{}", explained),
+            None => {
+                eprintln!("error: no provider '{}' in the graph", name);
+                process::exit(1);
+            }
+        },
+        ("gen", [path]) => generate(builder, Path::new(path)),
+        ("set", [name, path]) => {
+            let manifest = builder.set_manifest(name);
+            fs::write(path, manifest)
+                .unwrap_or_else(|err| panic!("failed write '{}': {}", path, err));
+        }
+        ("-h" | "--help", _) => usage(""),
+        _ => usage(&format!("invalid command '{}'", command)),
+    }
+}
+
+fn generate(builder: Builder, path: &Path) {
+    let out_dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.display().to_string(),
+        _ => ".".to_string(),
+    };
+    let out_file = path
+        .file_name()
+        .unwrap_or_else(|| usage("gen expects a file path"))
+        .to_string_lossy()
+        .to_string();
+    fs::create_dir_all(&out_dir)
+        .unwrap_or_else(|err| panic!("failed create directory '{}': {}", out_dir, err));
+    builder.out_dir(out_dir).out_file(out_file).build();
+}
+
+fn value(option: &str, value: Option<String>) -> String {
+    value.unwrap_or_else(|| usage(&format!("missing value for '{}'", option)))
+}
+
+fn usage(error: &str) -> ! {
+    if error.is_empty() {
+        println!("{}", USAGE);
+        process::exit(0);
+    }
+    eprintln!("error: {}\n\n{}", error, USAGE);
+    process::exit(2);
 }
@@ -0,0 +1,334 @@
+use proc_macro::TokenStream;
+use quote::{format_ident, quote, quote_spanned, ToTokens};
+use syn::{
+    parse_macro_input, spanned::Spanned, Expr, Field, Fields, GenericParam, Item, ItemStruct,
+    LitStr,
+};
+
+use wire_meta::{ConfigArgs, InjectArgs, ProviderArgs};
+
+#[proc_macro_attribute]
+pub fn provider(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let checked = ProviderArgs::parse(attr.into()).and_then(|args| {
+        // `#[injectable]` generates the constructor, not `#[provider]`
+        match (&args.constructor, &args.vis, &args.builder) {
+            (Some(constructor), _, _) => Err(syn::Error::new_spanned(
+                constructor,
+                "`constructor` only applies to `#[injectable]`",
+            )),
+            (_, Some(vis), _) => Err(syn::Error::new_spanned(
+                vis,
+                "`vis` only applies to `#[injectable]`",
+            )),
+            (_, _, Some(builder)) => Err(syn::Error::new_spanned(
+                builder,
+                "`builder` only applies to `#[injectable]`",
+            )),
+            _ => Ok(()),
+        }
+    });
+    if let Err(err) = checked {
+        let mut tokens = err.to_compile_error();
+        tokens.extend(proc_macro2::TokenStream::from(item));
+        return tokens.into();
+    }
+    item
+}
+
+#[proc_macro_attribute]
+pub fn config(attr: TokenStream, item: TokenStream) -> TokenStream {
+    if let Err(err) = ConfigArgs::parse(attr.into()) {
+        let mut tokens = err.to_compile_error();
+        tokens.extend(proc_macro2::TokenStream::from(item));
+        return tokens.into();
+    }
+    item
+}
+
+#[proc_macro_attribute]
+pub fn injectable(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let item = parse_macro_input!(item as Item);
+    match expand_injectable(attr.into(), item.clone()) {
+        Ok(expanded) => expanded.into(),
+        Err(err) => {
+            let mut tokens = err.to_compile_error();
+            tokens.extend(strip_helper_attrs(item));
+            tokens.into()
+        }
+    }
+}
+
+/// The item without the field attributes `#[injectable]` consumes, emitted next to
+/// an error so uses of the struct don't report errors of their own.
+fn strip_helper_attrs(mut item: Item) -> proc_macro2::TokenStream {
+    if let Item::Struct(ast) = &mut item {
+        for field in ast.fields.iter_mut() {
+            field.attrs.retain(|attr| {
+                !["inject", "default", "init"]
+                    .iter()
+                    .any(|name| attr.path().is_ident(name))
+            });
+        }
+    }
+    item.into_token_stream()
+}
+
+fn expand_injectable(
+    attr: proc_macro2::TokenStream,
+    item: Item,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let mut ast: ItemStruct = match item {
+        Item::Struct(item) => item,
+        Item::Enum(item) => {
+            return Err(syn::Error::new_spanned(
+                item.enum_token,
+                "`#[injectable]` only supports structs",
+            ))
+        }
+        Item::Union(item) => {
+            return Err(syn::Error::new_spanned(
+                item.union_token,
+                "`#[injectable]` only supports structs",
+            ))
+        }
+        item => {
+            return Err(syn::Error::new_spanned(
+                item,
+                "`#[injectable]` only supports structs",
+            ))
+        }
+    };
+    let args = ProviderArgs::parse(attr)?;
+    let constructor = args.constructor.unwrap_or_else(|| format_ident!("new"));
+    let vis = args.vis.unwrap_or_else(|| syn::parse_quote!(pub));
+    let ident = &ast.ident;
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    ast.attrs.retain(|attr| !attr.path().is_ident("injectable"));
+
+    let mut inject_params = Vec::new();
+    let mut injects = Vec::new();
+    let mut struct_fields = Vec::new();
+    for (index, field) in ast.fields.iter_mut().enumerate() {
+        // filter `inject` attr
+        let mut is_inject = false;
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("inject"))
+        {
+            if is_inject {
+                return Err(syn::Error::new_spanned(attr, "duplicate `#[inject]`"));
+            }
+            InjectArgs::from_attr(attr)?;
+            is_inject = true;
+        }
+        // tuple fields are passed as `arg0`, `arg1`, ...
+        let name = match field.ident.as_ref() {
+            Some(ident) => ident.clone(),
+            None => format_ident!("arg{}", index),
+        };
+        let ty = &field.ty;
+        if let Some(attr) = field.attrs.iter().find(|attr| {
+            is_inject && (attr.path().is_ident("default") || attr.path().is_ident("init"))
+        }) {
+            return Err(syn::Error::new_spanned(
+                attr,
+                "injected fields can't have `default` or `init`",
+            ));
+        }
+        let value = if is_inject {
+            // remove field attr #[inject]
+            field.attrs.retain(|attr| !attr.path().is_ident("inject"));
+            inject_params.push(quote! {#name: #ty});
+            injects.push((name.clone(), ty.clone()));
+            quote! {#name}
+        } else {
+            match field_init(field)? {
+                Some(value) => value,
+                // spanned to the field type, so a type without `Default` is reported there
+                None => {
+                    let ty = &field.ty;
+                    quote_spanned! {ty.span()=> <#ty as ::core::default::Default>::default()}
+                }
+            }
+        };
+        struct_fields.push(match field.ident.as_ref() {
+            Some(ident) => quote! {#ident: #value},
+            None => value,
+        });
+    }
+    let construct = match &ast.fields {
+        Fields::Named(_) => quote! { Self { #(#struct_fields),* } },
+        Fields::Unnamed(_) => quote! { Self(#(#struct_fields),*) },
+        Fields::Unit => quote! { Self },
+    };
+
+    let builder = match &args.builder {
+        Some(builder) if matches!(ast.fields, Fields::Unnamed(_)) => {
+            return Err(syn::Error::new_spanned(
+                builder,
+                "`builder` needs named fields to name its setters",
+            ))
+        }
+        Some(_) => expand_builder(&ast, &vis, &constructor, &injects),
+        None => quote! {},
+    };
+
+    let expanded = quote! {
+        #ast
+
+        impl #impl_generics #ident #ty_generics #where_clause {
+            #vis fn #constructor(#(#inject_params),*) -> Self {
+                #construct
+            }
+        }
+
+        #builder
+    };
+    Ok(expanded)
+}
+
+/// Typestate `<Struct>Builder`, an unset field has type `()` and a set one `(T,)`,
+/// so `build` only exists once every injected field is set.
+fn expand_builder(
+    ast: &ItemStruct,
+    vis: &syn::Visibility,
+    constructor: &syn::Ident,
+    injects: &[(syn::Ident, syn::Type)],
+) -> proc_macro2::TokenStream {
+    let ident = &ast.ident;
+    let builder = format_ident!("{}Builder", ident);
+    let (_, ty_generics, where_clause) = ast.generics.split_for_impl();
+    // struct parameters without their defaults, usable in impls
+    let params: Vec<_> = ast
+        .generics
+        .params
+        .iter()
+        .cloned()
+        .map(|mut param| {
+            match &mut param {
+                GenericParam::Type(param) => {
+                    param.eq_token = None;
+                    param.default = None;
+                }
+                GenericParam::Const(param) => {
+                    param.eq_token = None;
+                    param.default = None;
+                }
+                GenericParam::Lifetime(_) => {}
+            }
+            param
+        })
+        .collect();
+    let args: Vec<_> = ast
+        .generics
+        .params
+        .iter()
+        .map(|param| match param {
+            GenericParam::Type(param) => param.ident.to_token_stream(),
+            GenericParam::Const(param) => param.ident.to_token_stream(),
+            GenericParam::Lifetime(param) => param.lifetime.to_token_stream(),
+        })
+        .collect();
+    let states: Vec<_> = (0..injects.len())
+        .map(|i| format_ident!("__F{}", i))
+        .collect();
+    let names: Vec<_> = injects.iter().map(|(name, _)| name).collect();
+    let set = injects.iter().map(|(_, ty)| quote! {(#ty,)});
+    let unset = injects.iter().map(|_| quote! {()});
+
+    let setters = injects.iter().enumerate().map(|(i, (name, ty))| {
+        let others = states
+            .iter()
+            .enumerate()
+            .filter(|(j, _)| *j != i)
+            .map(|(_, state)| state);
+        let mut from: Vec<_> = states.iter().map(|state| quote! {#state}).collect();
+        from[i] = quote! {()};
+        let mut to = from.clone();
+        to[i] = quote! {(#ty,)};
+        let fields = names.iter().enumerate().map(|(j, field)| {
+            if j == i {
+                quote! {#field: (#field,)}
+            } else {
+                quote! {#field: self.#field}
+            }
+        });
+        quote! {
+            impl<#(#params,)* #(#others),*> #builder<#(#args,)* #(#from),*> #where_clause {
+                #vis fn #name(self, #name: #ty) -> #builder<#(#args,)* #(#to),*> {
+                    #builder {
+                        #(#fields,)*
+                        _marker: ::core::marker::PhantomData,
+                    }
+                }
+            }
+        }
+    });
+
+    quote! {
+        #vis struct #builder<#(#params,)* #(#states),*> #where_clause {
+            #(#names: #states,)*
+            _marker: ::core::marker::PhantomData<fn() -> #ident #ty_generics>,
+        }
+
+        impl<#(#params),*> #ident #ty_generics #where_clause {
+            #vis fn builder() -> #builder<#(#args,)* #(#unset),*> {
+                #builder {
+                    #(#names: (),)*
+                    _marker: ::core::marker::PhantomData,
+                }
+            }
+        }
+
+        #(#setters)*
+
+        impl<#(#params),*> #builder<#(#args,)* #(#set),*> #where_clause {
+            #vis fn build(self) -> #ident #ty_generics {
+                #ident::#constructor(#(self.#names.0),*)
+            }
+        }
+    }
+}
+
+/// Value of a non-injected field from `#[default(expr)]` or `#[init = "path::fn"]`,
+/// the attributes are removed from the field.
+fn field_init(field: &mut Field) -> syn::Result<Option<proc_macro2::TokenStream>> {
+    let mut value = None;
+    for attr in &field.attrs {
+        let is_init = attr.path().is_ident("default") || attr.path().is_ident("init");
+        if is_init && value.is_some() {
+            return Err(syn::Error::new_spanned(
+                attr,
+                "a field takes only one `default` or `init` attribute",
+            ));
+        }
+        if attr.path().is_ident("default") {
+            let expr: Expr = attr.parse_args()?;
+            value = Some(quote! {#expr});
+        } else if attr.path().is_ident("init") {
+            let lit: LitStr = match &attr.meta {
+                syn::Meta::NameValue(syn::MetaNameValue {
+                    value:
+                        Expr::Lit(syn::ExprLit {
+                            lit: syn::Lit::Str(lit),
+                            ..
+                        }),
+                    ..
+                }) => lit.clone(),
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        attr,
+                        "expected `#[init = \"path::to::fn\"]`",
+                    ))
+                }
+            };
+            let path: syn::Path = lit.parse()?;
+            value = Some(quote_spanned! {lit.span()=> #path()});
+        }
+    }
+    field
+        .attrs
+        .retain(|attr| !attr.path().is_ident("default") && !attr.path().is_ident("init"));
+    Ok(value)
+}
@@ -0,0 +1,241 @@
+//! Grammar of the wire attributes, shared by the `wire` macros which report
+//! mistakes at compile time and by wire-build which reads them from sources.
+
+use proc_macro2::TokenStream;
+use syn::{
+    meta::ParseNestedMeta, parenthesized, parse::Parser, spanned::Spanned, token, Attribute, LitStr,
+};
+
+/// Arguments of `#[provider(...)]` and `#[injectable(...)]`
+#[derive(Default)]
+pub struct ProviderArgs {
+    /// `config` or `config("section")`
+    pub config: Option<Option<LitStr>>,
+    /// `export` or `export(context = "name")`
+    pub export: Option<Option<LitStr>>,
+    /// `reload`, kept to span errors
+    pub reload: Option<syn::Ident>,
+    pub profile: Option<LitStr>,
+    pub rename: Option<LitStr>,
+    /// `constructor = "name"` of the generated constructor, injectables only
+    pub constructor: Option<syn::Ident>,
+    /// `vis = "pub(crate)"` of the generated constructor, injectables only
+    pub vis: Option<syn::Visibility>,
+    /// `builder`, also generate a typestate builder, injectables only
+    pub builder: Option<syn::Ident>,
+}
+
+impl ProviderArgs {
+    pub fn from_attr(attr: &Attribute) -> syn::Result<Self> {
+        Self::parse(attr_args(attr)?)
+    }
+
+    pub fn parse(args: TokenStream) -> syn::Result<Self> {
+        let parsed = Self::parse_args(args, false)?;
+        if parsed.config.is_some() {
+            let construct = parsed
+                .constructor
+                .as_ref()
+                .map(|constructor| constructor.span())
+                .or_else(|| parsed.vis.as_ref().map(|vis| vis.span()))
+                .or_else(|| parsed.builder.as_ref().map(|builder| builder.span()));
+            if let Some(span) = construct {
+                return Err(syn::Error::new(
+                    span,
+                    "`constructor`, `vis` and `builder` don't apply to `config` providers",
+                ));
+            }
+        }
+        if let (Some(reload), None) = (&parsed.reload, &parsed.config) {
+            return Err(syn::Error::new_spanned(
+                reload,
+                "`reload` only applies to `config` providers",
+            ));
+        }
+        Ok(parsed)
+    }
+
+    /// `config_only` accepts the arguments `#[config]` takes after its section
+    fn parse_args(args: TokenStream, config_only: bool) -> syn::Result<Self> {
+        let mut parsed = Self::default();
+        let parser = syn::meta::parser(|meta| {
+            if config_only && !(meta.path.is_ident("reload") || meta.path.is_ident("profile")) {
+                return Err(meta.error("config takes only a section name, `reload` and `profile`"));
+            }
+            if meta.path.is_ident("config") {
+                check_unset(&meta, parsed.config.is_some())?;
+                parsed.config = Some(parse_optional_paren(&meta)?);
+            } else if meta.path.is_ident("export") {
+                check_unset(&meta, parsed.export.is_some())?;
+                let mut context = None;
+                if meta.input.peek(token::Paren) {
+                    meta.parse_nested_meta(|meta| {
+                        if meta.path.is_ident("context") {
+                            check_unset(&meta, context.is_some())?;
+                            context = Some(meta.value()?.parse()?);
+                            Ok(())
+                        } else {
+                            Err(meta.error("unsupported export argument, expected `context`"))
+                        }
+                    })?;
+                }
+                parsed.export = Some(context);
+            } else if meta.path.is_ident("reload") {
+                check_unset(&meta, parsed.reload.is_some())?;
+                parsed.reload = meta.path.get_ident().cloned();
+            } else if meta.path.is_ident("profile") {
+                check_unset(&meta, parsed.profile.is_some())?;
+                parsed.profile = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("rename") {
+                check_unset(&meta, parsed.rename.is_some())?;
+                match parse_optional_paren(&meta)? {
+                    Some(lit) => parsed.rename = Some(lit),
+                    None => return Err(meta.error("expected `rename(\"name\")`")),
+                }
+            } else if meta.path.is_ident("constructor") {
+                check_unset(&meta, parsed.constructor.is_some())?;
+                let lit: LitStr = meta.value()?.parse()?;
+                parsed.constructor = Some(lit.parse()?);
+            } else if meta.path.is_ident("builder") {
+                check_unset(&meta, parsed.builder.is_some())?;
+                parsed.builder = meta.path.get_ident().cloned();
+            } else if meta.path.is_ident("vis") {
+                check_unset(&meta, parsed.vis.is_some())?;
+                let lit: LitStr = meta.value()?.parse()?;
+                parsed.vis = Some(lit.parse()?);
+            } else {
+                return Err(meta.error(
+                    "unsupported provider argument, expected `config`, `export`, `reload`, `profile`, `rename`, `constructor`, `vis` or `builder`",
+                ));
+            }
+            Ok(())
+        });
+        parser.parse2(args)?;
+        Ok(parsed)
+    }
+}
+
+/// Arguments of `#[config(...)]`, a shorthand for `#[provider(config(...))]`:
+/// `#[config]`, `#[config("section")]` and `#[config("section", reload)]`
+pub struct ConfigArgs;
+
+impl ConfigArgs {
+    pub fn from_attr(attr: &Attribute) -> syn::Result<ProviderArgs> {
+        Self::parse(attr_args(attr)?)
+    }
+
+    pub fn parse(args: TokenStream) -> syn::Result<ProviderArgs> {
+        let mut section = None;
+        let mut parsed = ProviderArgs::default();
+        let parser = |input: syn::parse::ParseStream| {
+            if input.peek(LitStr) {
+                section = Some(input.parse::<LitStr>()?);
+                if !input.is_empty() {
+                    input.parse::<token::Comma>()?;
+                }
+            }
+            let rest: TokenStream = input.parse()?;
+            parsed = ProviderArgs::parse_args(rest, true)?;
+            Ok(())
+        };
+        parser.parse2(args)?;
+        parsed.config = Some(section);
+        Ok(parsed)
+    }
+}
+
+/// Arguments of `#[inject(...)]` on a field
+#[derive(Default)]
+pub struct InjectArgs {
+    pub manual: bool,
+    /// `default` or `default = "path::fn"`
+    pub default: Option<Option<LitStr>>,
+    pub name: Option<LitStr>,
+    pub value: Option<LitStr>,
+}
+
+impl InjectArgs {
+    pub fn from_attr(attr: &Attribute) -> syn::Result<Self> {
+        let mut parsed = Self::default();
+        // bare `#[inject]`
+        if matches!(attr.meta, syn::Meta::Path(_)) {
+            return Ok(parsed);
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("manual") {
+                check_unset(&meta, parsed.manual)?;
+                parsed.manual = true;
+            } else if meta.path.is_ident("default") {
+                check_unset(&meta, parsed.default.is_some())?;
+                parsed.default = Some(if meta.input.peek(token::Eq) {
+                    Some(meta.value()?.parse()?)
+                } else {
+                    None
+                });
+            } else if meta.path.is_ident("name") {
+                check_unset(&meta, parsed.name.is_some())?;
+                parsed.name = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("value") {
+                check_unset(&meta, parsed.value.is_some())?;
+                parsed.value = Some(meta.value()?.parse()?);
+            } else {
+                return Err(meta.error(
+                    "unsupported inject argument, expected `manual`, `default`, `name` or `value`",
+                ));
+            }
+            Ok(())
+        })?;
+
+        if parsed.value.is_some() && (parsed.manual || parsed.default.is_some()) {
+            return Err(syn::Error::new_spanned(
+                attr,
+                "`value` can't be combined with `manual` or `default`",
+            ));
+        }
+        if parsed.default.is_some() && !parsed.manual {
+            return Err(syn::Error::new_spanned(
+                attr,
+                "`default` only applies to `manual` injects",
+            ));
+        }
+        Ok(parsed)
+    }
+}
+
+/// Tokens inside the parentheses, empty for a bare `#[name]`
+fn attr_args(attr: &Attribute) -> syn::Result<TokenStream> {
+    match &attr.meta {
+        syn::Meta::Path(_) => Ok(TokenStream::new()),
+        syn::Meta::List(list) => Ok(list.tokens.clone()),
+        syn::Meta::NameValue(_) => Err(syn::Error::new_spanned(
+            attr,
+            "expected parenthesized arguments",
+        )),
+    }
+}
+
+fn check_unset(meta: &ParseNestedMeta, set: bool) -> syn::Result<()> {
+    if set {
+        let name = meta
+            .path
+            .get_ident()
+            .map(|v| v.to_string())
+            .unwrap_or_default();
+        return Err(meta.error(format!("duplicate `{}`", name)));
+    }
+    Ok(())
+}
+
+/// `name` or `name("value")`
+fn parse_optional_paren(meta: &ParseNestedMeta) -> syn::Result<Option<LitStr>> {
+    if !meta.input.peek(token::Paren) {
+        return Ok(None);
+    }
+    let content;
+    parenthesized!(content in meta.input);
+    let lit: LitStr = content.parse()?;
+    if !content.is_empty() {
+        return Err(content.error("expected a single string literal"));
+    }
+    Ok(Some(lit))
+}
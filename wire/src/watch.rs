@@ -0,0 +1,90 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex, RwLock, TryLockError,
+};
+
+type Subscriber<T> = Arc<dyn Fn(&T) + Send + Sync>;
+
+/// Handle to a reloadable config section, updated by `reload_config` of the
+/// generated contexts. A crate with a `reload` section compares every section on
+/// reload, so all config sections need `PartialEq`.
+pub struct Watch<T> {
+    inner: Arc<WatchInner<T>>,
+}
+
+struct WatchInner<T> {
+    value: RwLock<Arc<T>>,
+    subscribers: Mutex<Vec<Subscriber<T>>>,
+    /// held by the thread calling the subscribers
+    notifying: Mutex<()>,
+    /// a value was set since the subscribers were last called
+    pending: AtomicBool,
+}
+
+impl<T> Clone for Watch<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T> Watch<T> {
+    pub fn new(value: T) -> Self {
+        Self {
+            inner: Arc::new(WatchInner {
+                value: RwLock::new(Arc::new(value)),
+                subscribers: Mutex::new(Vec::new()),
+                notifying: Mutex::new(()),
+                pending: AtomicBool::new(false),
+            }),
+        }
+    }
+
+    /// Current value of the section.
+    pub fn get(&self) -> Arc<T> {
+        self.inner.value.read().unwrap().clone()
+    }
+
+    /// Register a callback invoked with the new value after every reload.
+    pub fn subscribe<F: Fn(&T) + Send + Sync + 'static>(&self, f: F) {
+        self.inner.subscribers.lock().unwrap().push(Arc::new(f));
+    }
+
+    /// Swap the value and notify the subscribers.
+    pub fn replace(&self, value: T) {
+        self.set(value);
+        self.notify();
+    }
+
+    /// Swap the value without notifying, `reload_config` sets watches under the
+    /// config lock so they change in the same order as the config.
+    pub fn set(&self, value: T) {
+        *self.inner.value.write().unwrap() = Arc::new(value);
+    }
+
+    /// Call the subscribers with the current value, without the value or subscriber
+    /// locks held so they may read the watch, subscribe or reload again. One thread
+    /// notifies at a time, a notify meanwhile, also from a subscriber, is taken up
+    /// by its loop, so the last call always gets the latest value.
+    pub fn notify(&self) {
+        let inner = &self.inner;
+        inner.pending.store(true, Ordering::SeqCst);
+        // checked again after unlocking, a notify that found the lock held may have
+        // come after the last check under it
+        while inner.pending.load(Ordering::SeqCst) {
+            let _notifying = match inner.notifying.try_lock() {
+                Ok(guard) => guard,
+                Err(TryLockError::Poisoned(err)) => err.into_inner(),
+                Err(TryLockError::WouldBlock) => return,
+            };
+            while inner.pending.swap(false, Ordering::SeqCst) {
+                let value = self.get();
+                let subscribers = inner.subscribers.lock().unwrap().clone();
+                for subscriber in subscribers {
+                    subscriber(&value);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,22 @@
+use std::sync::Arc;
+use wire::injectable;
+
+pub struct Repo;
+pub struct Clock;
+
+#[injectable(builder)]
+pub struct Service {
+    #[inject]
+    repo: Arc<Repo>,
+    #[inject]
+    clock: Arc<Clock>,
+}
+
+fn main() {
+    // setters go in any order
+    let service = Service::builder()
+        .clock(Arc::new(Clock))
+        .repo(Arc::new(Repo))
+        .build();
+    let _ = (&service.repo, &service.clock);
+}
@@ -0,0 +1,18 @@
+use std::sync::Arc;
+use wire::injectable;
+
+pub struct Repo;
+pub struct Clock;
+
+#[injectable(builder)]
+pub struct Service {
+    #[inject]
+    repo: Arc<Repo>,
+    #[inject]
+    clock: Arc<Clock>,
+}
+
+fn main() {
+    // `clock` is never set, so there is no `build`
+    let _service = Service::builder().repo(Arc::new(Repo)).build();
+}
@@ -0,0 +1,6 @@
+use wire::injectable;
+
+#[injectable(config, constructor = "create")]
+pub struct Settings {}
+
+fn main() {}
@@ -0,0 +1,6 @@
+use wire::config;
+
+#[config("app", export)]
+pub struct Settings {}
+
+fn main() {}
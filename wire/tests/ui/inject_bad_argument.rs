@@ -0,0 +1,9 @@
+use wire::injectable;
+
+#[injectable]
+pub struct Service {
+    #[inject(manual, nmae = "repo")]
+    repo: u8,
+}
+
+fn main() {}
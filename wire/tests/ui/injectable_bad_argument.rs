@@ -0,0 +1,17 @@
+use std::sync::Arc;
+use wire::injectable;
+
+pub struct Repo;
+
+#[injectable(bogus)]
+pub struct Service {
+    #[inject]
+    repo: Arc<Repo>,
+}
+
+// the struct is still emitted, its uses report no errors of their own
+fn repo(service: &Service) -> &Arc<Repo> {
+    &service.repo
+}
+
+fn main() {}
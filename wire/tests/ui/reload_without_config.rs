@@ -0,0 +1,6 @@
+use wire::provider;
+
+#[provider(reload)]
+pub struct Settings {}
+
+fn main() {}